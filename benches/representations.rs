@@ -0,0 +1,193 @@
+//! Compares the representations available in the crate on the same datasets. Run with
+//! `cargo bench`; every line reports the average time of one operation. The frozen and adaptive
+//! trees are read-only, so they only take part in the lookups
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use preftree::{CountedPrefixTree, InternedPrefixTree, OrderedPrefixTree, PrefixTree};
+
+const ROUNDS: u32 = 5;
+
+/// Route-like keys such as `api/v3/users/17/posts`, where the segment names repeat a lot
+fn routes(count: usize) -> Vec<String> {
+    const RESOURCES: [&str; 6] = ["users", "posts", "comments", "teams", "files", "tags"];
+    (0..count)
+        .map(|i| {
+            format!(
+                "api/v{}/{}/{}/{}",
+                i % 4,
+                RESOURCES[i % RESOURCES.len()],
+                i / 24,
+                RESOURCES[(i / 7) % RESOURCES.len()]
+            )
+        })
+        .collect()
+}
+
+/// Runs the function `ROUNDS` times after a warm-up run and prints the average time per operation
+fn bench(name: &str, operations: usize, mut f: impl FnMut() -> usize) {
+    let mut checksum = f();
+    let mut total = Duration::default();
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        checksum = checksum.wrapping_add(f());
+        total += start.elapsed();
+    }
+    let per_operation = total / ROUNDS / operations as u32;
+    println!(
+        "{:<40} {:>10?} (checksum {})",
+        name, per_operation, checksum
+    );
+}
+
+fn main() {
+    let keys = routes(20_000);
+    let n = keys.len();
+
+    bench("insert/flat HashMap", n, || {
+        let mut map = HashMap::new();
+        for (i, key) in keys.iter().enumerate() {
+            map.insert(key.as_bytes().to_vec(), i);
+        }
+        map.len()
+    });
+    bench("insert/PrefixTree<u8> (insert_copied)", n, || {
+        let mut tree = PrefixTree::new();
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert_copied(key.as_bytes(), i);
+        }
+        tree.subtrees.len()
+    });
+    bench("insert/PrefixTree<String> (segments)", n, || {
+        let mut tree: PrefixTree<String, _> = PrefixTree::new();
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert(key.split('/'), i);
+        }
+        tree.subtrees.len()
+    });
+    bench("insert/OrderedPrefixTree<u8>", n, || {
+        let mut tree: OrderedPrefixTree<u8, _> = OrderedPrefixTree::new();
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert(key.as_bytes(), i);
+        }
+        tree.len()
+    });
+    bench("insert/CountedPrefixTree<u8>", n, || {
+        let mut tree: CountedPrefixTree<u8, _> = CountedPrefixTree::new();
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert(key.as_bytes(), i);
+        }
+        tree.len()
+    });
+    bench("insert/InternedPrefixTree", n, || {
+        let mut tree = InternedPrefixTree::new();
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert(key.split('/'), i);
+        }
+        tree.pool_len()
+    });
+
+    let map: HashMap<_, _> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| (key.as_bytes().to_vec(), i))
+        .collect();
+    let mut bytes = PrefixTree::new();
+    let mut segments: PrefixTree<String, _> = PrefixTree::new();
+    let mut interned = InternedPrefixTree::new();
+    let mut ordered: OrderedPrefixTree<u8, _> = OrderedPrefixTree::new();
+    let mut counted: CountedPrefixTree<u8, _> = CountedPrefixTree::new();
+    for (i, key) in keys.iter().enumerate() {
+        bytes.insert_copied(key.as_bytes(), i);
+        segments.insert(key.split('/'), i);
+        interned.insert(key.split('/'), i);
+        ordered.insert(key.as_bytes(), i);
+        counted.insert(key.as_bytes(), i);
+    }
+    let byte_tree = || {
+        let mut tree = PrefixTree::new();
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert_copied(key.as_bytes(), i);
+        }
+        tree
+    };
+    let frozen = byte_tree().freeze();
+    let adaptive = byte_tree().adapt();
+
+    bench("get_exact_match/flat HashMap", n, || {
+        keys.iter().filter_map(|key| map.get(key.as_bytes())).sum()
+    });
+    bench("get_exact_match/PrefixTree<u8>", n, || {
+        keys.iter()
+            .filter_map(|key| bytes.get_exact_match_slice(key.as_bytes()))
+            .sum()
+    });
+    bench("get_exact_match/PrefixTree<String>", n, || {
+        keys.iter()
+            .filter_map(|key| segments.get_exact_match(key.split('/').map(String::from)))
+            .sum()
+    });
+    bench("get_exact_match/OrderedPrefixTree<u8>", n, || {
+        keys.iter()
+            .filter_map(|key| ordered.get_exact_match(key.as_bytes()))
+            .sum()
+    });
+    bench("get_exact_match/CountedPrefixTree<u8>", n, || {
+        keys.iter()
+            .filter_map(|key| counted.get_exact_match(key.as_bytes()))
+            .sum()
+    });
+    bench("get_exact_match/FrozenPrefixTree<u8>", n, || {
+        keys.iter()
+            .filter_map(|key| frozen.get_exact_match(key.as_bytes()))
+            .sum()
+    });
+    bench("get_exact_match/AdaptivePrefixTree<u8>", n, || {
+        keys.iter()
+            .filter_map(|key| adaptive.get_exact_match(key.as_bytes()))
+            .sum()
+    });
+    bench("get_exact_match/InternedPrefixTree", n, || {
+        keys.iter()
+            .filter_map(|key| interned.get_exact_match(key.split('/')))
+            .sum()
+    });
+
+    bench("remove_exact_match/PrefixTree<u8>", n, || {
+        let mut tree = PrefixTree::new();
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert_copied(key.as_bytes(), i);
+        }
+        keys.iter()
+            .filter_map(|key| tree.remove_exact_match(key.as_bytes()))
+            .sum()
+    });
+    bench("remove_exact_match/OrderedPrefixTree<u8>", n, || {
+        let mut tree: OrderedPrefixTree<u8, _> = OrderedPrefixTree::new();
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert(key.as_bytes(), i);
+        }
+        keys.iter()
+            .filter_map(|key| tree.remove_exact_match(key.as_bytes()))
+            .sum()
+    });
+    bench("remove_exact_match/CountedPrefixTree<u8>", n, || {
+        let mut tree: CountedPrefixTree<u8, _> = CountedPrefixTree::new();
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert(key.as_bytes(), i);
+        }
+        keys.iter()
+            .filter_map(|key| tree.remove_exact_match(key.as_bytes()))
+            .sum()
+    });
+    bench("remove_exact_match/InternedPrefixTree", n, || {
+        let mut tree = InternedPrefixTree::new();
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert(key.split('/'), i);
+        }
+        keys.iter()
+            .filter_map(|key| tree.remove_exact_match(key.split('/')))
+            .sum()
+    });
+}
@@ -0,0 +1,310 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+use crate::{PrefixMapRead, PrefixTree};
+
+/// The largest number of children that are searched linearly
+const SMALL_FANOUT: usize = 4;
+/// The largest number of children that are binary searched; above that they are hashed
+const SORTED_FANOUT: usize = 32;
+
+/// A read-only prefix tree built by `PrefixTree::adapt`, where the layout is chosen from the shape
+/// of the keys instead of being fixed: every node stores its children as a small list searched
+/// linearly, a sorted array searched by bisection or a hash map, depending on how many children
+/// it has, and the chains of nodes without values are path-compressed when they make up a large
+/// enough part of the tree. `stats` reports the decisions
+#[derive(Debug, Clone)]
+pub struct AdaptivePrefixTree<K, V> {
+    root: AdaptiveNode<K, V>,
+    len: usize,
+    stats: LayoutStats,
+}
+
+/// The layout chosen by `PrefixTree::adapt`, as reported by `AdaptivePrefixTree::stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LayoutStats {
+    /// The number of nodes after path compression
+    pub nodes: usize,
+    /// The nodes whose children are searched linearly (including the leaves)
+    pub small_nodes: usize,
+    /// The nodes whose children are binary searched
+    pub sorted_nodes: usize,
+    /// The nodes whose children are hashed
+    pub hashed_nodes: usize,
+    /// Whether chains of nodes without values were merged into their children
+    pub path_compression: bool,
+    /// The number of nodes merged away by path compression
+    pub compressed_nodes: usize,
+}
+
+#[derive(Debug, Clone)]
+struct AdaptiveNode<K, V> {
+    /// The items after the one leading to the node that were merged into it by path compression;
+    /// the merged nodes had no values and no other children
+    label: Vec<K>,
+    value: Option<V>,
+    children: Children<K, V>,
+}
+
+#[derive(Debug, Clone)]
+enum Children<K, V> {
+    Small(Vec<(K, AdaptiveNode<K, V>)>),
+    /// Ordered by the items
+    Sorted(Vec<(K, AdaptiveNode<K, V>)>),
+    Hashed(HashMap<K, AdaptiveNode<K, V>>),
+}
+
+impl<K: Hash + Eq + Ord, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Converts the tree into its adaptive read-only representation. The shape of the tree is
+    /// measured with `stats` first: path compression is used when at least a quarter of the nodes
+    /// are chain nodes, and the layout of every node's children follows their number
+    pub fn adapt(self) -> AdaptivePrefixTree<K, V> {
+        let shape = self.stats();
+        let mut stats = LayoutStats {
+            path_compression: shape.chain_nodes * 4 >= shape.nodes,
+            ..LayoutStats::default()
+        };
+        let root = adapt_node(self, Vec::new(), &mut stats);
+        AdaptivePrefixTree {
+            root,
+            len: shape.entries,
+            stats,
+        }
+    }
+}
+
+fn adapt_node<K: Hash + Eq + Ord, V, S: BuildHasher + Clone>(
+    mut root: PrefixTree<K, V, S>,
+    mut label: Vec<K>,
+    stats: &mut LayoutStats,
+) -> AdaptiveNode<K, V> {
+    stats.nodes += 1;
+    let mut children: Vec<_> = root
+        .subtrees
+        .drain()
+        .map(|(item, mut subtree)| {
+            let mut label = Vec::new();
+            if stats.path_compression {
+                while subtree.value.is_none() && subtree.subtrees.len() == 1 {
+                    let (item, next) = subtree.subtrees.drain().next().unwrap();
+                    label.push(item);
+                    subtree = next;
+                    stats.compressed_nodes += 1;
+                }
+            }
+            (item, adapt_node(subtree, label, stats))
+        })
+        .collect();
+    let children = if children.len() <= SMALL_FANOUT {
+        stats.small_nodes += 1;
+        Children::Small(children)
+    } else if children.len() <= SORTED_FANOUT {
+        stats.sorted_nodes += 1;
+        children.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        Children::Sorted(children)
+    } else {
+        stats.hashed_nodes += 1;
+        Children::Hashed(children.into_iter().collect())
+    };
+    label.shrink_to_fit();
+    AdaptiveNode {
+        label,
+        value: root.value,
+        children,
+    }
+}
+
+impl<K: Hash + Eq + Ord, V> Children<K, V> {
+    fn get(&self, item: &K) -> Option<&AdaptiveNode<K, V>> {
+        match self {
+            Self::Small(children) => children
+                .iter()
+                .find(|(child, _)| child == item)
+                .map(|(_, node)| node),
+            Self::Sorted(children) => {
+                let index = children
+                    .binary_search_by(|(child, _)| child.cmp(item))
+                    .ok()?;
+                Some(&children[index].1)
+            }
+            Self::Hashed(children) => children.get(item),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Ord, V> AdaptivePrefixTree<K, V> {
+    /// Returns the value at exactly the given sequence
+    pub fn get_exact_match<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        let mut sequence = sequence.into_iter();
+        let mut node = &self.root;
+        while let Some(item) = sequence.next() {
+            node = node.children.get(item.borrow())?;
+            for merged in &node.label {
+                if sequence.next()?.borrow() != merged {
+                    return None;
+                }
+            }
+        }
+        node.value.as_ref()
+    }
+
+    /// Returns the value associated with the shortest prefix of the given sequence
+    pub fn get_by_shortest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        let mut sequence = sequence.into_iter();
+        let mut node = &self.root;
+        loop {
+            if let Some(value) = &node.value {
+                return Some(value);
+            }
+            node = node.children.get(sequence.next()?.borrow())?;
+            for merged in &node.label {
+                if sequence.next()?.borrow() != merged {
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Returns the value associated with the longest prefix of the given sequence
+    pub fn get_by_longest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        let mut sequence = sequence.into_iter();
+        let mut node = &self.root;
+        let mut found = node.value.as_ref();
+        while let Some(item) = sequence.next() {
+            node = match node.children.get(item.borrow()) {
+                Some(child) => child,
+                None => break,
+            };
+            // The merged nodes have no values, so a sequence ending among them matches nothing more
+            let on_label = node.label.iter().all(|merged| {
+                sequence
+                    .next()
+                    .map_or(false, |item| item.borrow() == merged)
+            });
+            if !on_label {
+                break;
+            }
+            found = node.value.as_ref().or(found);
+        }
+        found
+    }
+
+    /// Returns the number of stored values
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the layout decisions made when the tree was built
+    pub fn stats(&self) -> LayoutStats {
+        self.stats
+    }
+}
+
+impl<K: Hash + Eq + Ord, V> PrefixMapRead<K, V> for AdaptivePrefixTree<K, V> {
+    fn get_exact_match(&self, key: &[K]) -> Option<&V> {
+        AdaptivePrefixTree::get_exact_match(self, key)
+    }
+
+    fn get_by_shortest_prefix(&self, key: &[K]) -> Option<&V> {
+        AdaptivePrefixTree::get_by_shortest_prefix(self, key)
+    }
+
+    fn len(&self) -> usize {
+        AdaptivePrefixTree::len(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adapt() {
+        let mut tree = PrefixTree::new();
+        let mut keys: Vec<String> = ('a'..='z').chain('A'..='Z').map(String::from).collect();
+        keys.extend(('a'..='h').map(|item| format!("a{}", item)));
+        keys.extend(["blocked/path", "blocked/pass", "c", "cd"].map(String::from));
+        for (index, key) in keys.iter().enumerate() {
+            tree.insert(key.chars(), index);
+        }
+        let queries = [
+            "",
+            "a",
+            "ab",
+            "abc",
+            "ah",
+            "ai",
+            "bl",
+            "blocked/",
+            "blocked/path",
+            "blocked/pathx",
+            "blocked/px",
+            "cd",
+            "cde",
+            "Z",
+        ];
+        let expected: Vec<_> = queries
+            .iter()
+            .map(|query| {
+                (
+                    tree.get_exact_match(query.chars()).copied(),
+                    tree.get_by_shortest_prefix(query.chars()).copied(),
+                    tree.get_by_longest_prefix(query.chars()).copied(),
+                )
+            })
+            .collect();
+
+        let adaptive = tree.adapt();
+        let found: Vec<_> = queries
+            .iter()
+            .map(|query| {
+                (
+                    adaptive.get_exact_match(query.chars()).copied(),
+                    adaptive.get_by_shortest_prefix(query.chars()).copied(),
+                    adaptive.get_by_longest_prefix(query.chars()).copied(),
+                )
+            })
+            .collect();
+        assert_eq!(found, expected);
+
+        let stats = adaptive.stats();
+        // The root has 52 children and "a" has 8; the chain nodes are too few to compress
+        assert!(!stats.path_compression);
+        assert_eq!((stats.hashed_nodes, stats.sorted_nodes), (1, 1));
+        assert_eq!(stats.nodes, stats.small_nodes + 2);
+
+        let mut chains = PrefixTree::new();
+        for key in ["blocked/path", "blocked/pass", "open"] {
+            chains.insert(key.chars(), ());
+        }
+        let adaptive = chains.adapt();
+        assert_eq!(
+            adaptive.stats(),
+            LayoutStats {
+                nodes: 5,
+                small_nodes: 5,
+                sorted_nodes: 0,
+                hashed_nodes: 0,
+                path_compression: true,
+                compressed_nodes: 14,
+            }
+        );
+        assert_eq!(adaptive.get_exact_match("blocked/pass".chars()), Some(&()));
+        assert_eq!(adaptive.get_by_longest_prefix("blocked/pa".chars()), None);
+        assert_eq!(adaptive.get_exact_match("op".chars()), None);
+    }
+}
@@ -0,0 +1,83 @@
+use std::any::Any;
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+use crate::{IntoKeyItem, PrefixTree};
+
+pub type AnyValue = Box<dyn Any + Send + Sync>;
+
+/// A prefix tree that can hold values of different types under different keys; the values are
+/// retrieved by naming their type, and a lookup with the wrong type finds nothing
+#[derive(Debug, Default)]
+pub struct AnyPrefixTree<K: Hash + Eq> {
+    tree: PrefixTree<K, AnyValue>,
+}
+
+impl<K: Hash + Eq> AnyPrefixTree<K> {
+    pub fn new() -> Self {
+        Self {
+            tree: PrefixTree::new(),
+        }
+    }
+
+    /// Same as `PrefixTree::insert`; the previous value is returned type-erased since it may be
+    /// of any type
+    pub fn insert<I: IntoKeyItem<K>, T: Any + Send + Sync>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        value: T,
+    ) -> Option<AnyValue> {
+        self.tree.insert(sequence, Box::new(value))
+    }
+
+    /// Returns the value at the exact key if there is one and it is of type `T`
+    pub fn get<T: Any, I: Borrow<K>>(&self, sequence: impl IntoIterator<Item = I>) -> Option<&T> {
+        self.tree.get_exact_match(sequence)?.downcast_ref()
+    }
+
+    /// Returns the value at the exact key if there is one and it is of type `T`
+    pub fn get_mut<T: Any, I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&mut T> {
+        self.tree.get_exact_match_mut(sequence)?.downcast_mut()
+    }
+
+    /// Returns the value at the shortest prefix of the sequence if there is one and it is of type
+    /// `T` (a value of another type at the shortest prefix is not skipped)
+    pub fn get_by_shortest_prefix<T: Any, I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&T> {
+        self.tree.get_by_shortest_prefix(sequence)?.downcast_ref()
+    }
+
+    /// Removes the value at the exact key, whatever its type
+    pub fn remove_exact_match<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<AnyValue> {
+        self.tree.remove_exact_match(sequence)
+    }
+
+    pub fn tree(&self) -> &PrefixTree<K, AnyValue> {
+        &self.tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_any_prefix_tree() {
+        let mut tree = AnyPrefixTree::<&str>::new();
+        tree.insert(["plugins", "a"], 1u32);
+        tree.insert(["plugins", "b"], "name");
+
+        assert_eq!(tree.get::<u32, _>(["plugins", "a"]), Some(&1));
+        assert_eq!(tree.get::<&str, _>(["plugins", "a"]), None);
+        *tree.get_mut::<&str, _>(["plugins", "b"]).unwrap() = "renamed";
+        assert_eq!(tree.get::<&str, _>(["plugins", "b"]), Some(&"renamed"));
+    }
+}
@@ -0,0 +1,127 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::{IntoKeyItem, PrefixTree};
+
+/// A way to store values of type `V` in a different (usually smaller) representation, e.g.
+/// serialized and compressed bytes
+pub trait ValueCodec<V> {
+    type Encoded;
+
+    fn encode(&self, value: V) -> Self::Encoded;
+    fn decode(&self, encoded: &Self::Encoded) -> V;
+}
+
+/// A prefix tree that keeps its values encoded with a `ValueCodec` and decodes them on access,
+/// trading CPU time for memory when the values are large. Lookups return owned, decoded values
+#[derive(Debug)]
+pub struct CodecPrefixTree<K: Hash + Eq, V, C: ValueCodec<V>> {
+    tree: PrefixTree<K, C::Encoded>,
+    codec: C,
+    values: PhantomData<fn(V) -> V>,
+}
+
+impl<K: Hash + Eq, V, C: ValueCodec<V>> CodecPrefixTree<K, V, C> {
+    pub fn new(codec: C) -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            codec,
+            values: PhantomData,
+        }
+    }
+
+    /// Same as `PrefixTree::insert`, but the value is encoded before being stored
+    pub fn insert<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        value: V,
+    ) -> Option<V> {
+        let previous = self.tree.insert(sequence, self.codec.encode(value))?;
+        Some(self.codec.decode(&previous))
+    }
+
+    /// Same as `PrefixTree::get_exact_match`, but returns the decoded value
+    pub fn get_exact_match<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<V> {
+        let encoded = self.tree.get_exact_match(sequence)?;
+        Some(self.codec.decode(encoded))
+    }
+
+    /// Same as `PrefixTree::get_by_shortest_prefix`, but returns the decoded value
+    pub fn get_by_shortest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<V> {
+        let encoded = self.tree.get_by_shortest_prefix(sequence)?;
+        Some(self.codec.decode(encoded))
+    }
+
+    /// Same as `PrefixTree::remove_exact_match`, but returns the decoded value
+    pub fn remove_exact_match<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<V> {
+        let encoded = self.tree.remove_exact_match(sequence)?;
+        Some(self.codec.decode(&encoded))
+    }
+
+    /// Returns the underlying tree of encoded values
+    pub fn tree(&self) -> &PrefixTree<K, C::Encoded> {
+        &self.tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stores strings as (byte, repetitions) runs
+    struct RunLength;
+
+    impl ValueCodec<String> for RunLength {
+        type Encoded = Vec<(u8, usize)>;
+
+        fn encode(&self, value: String) -> Self::Encoded {
+            let mut runs: Vec<(u8, usize)> = Vec::new();
+            for byte in value.into_bytes() {
+                match runs.last_mut() {
+                    Some((last, count)) if *last == byte => *count += 1,
+                    _ => runs.push((byte, 1)),
+                }
+            }
+            runs
+        }
+
+        fn decode(&self, encoded: &Self::Encoded) -> String {
+            let bytes = encoded
+                .iter()
+                .flat_map(|&(byte, count)| std::iter::repeat(byte).take(count))
+                .collect();
+            String::from_utf8(bytes).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_codec_prefix_tree() {
+        let mut tree = CodecPrefixTree::new(RunLength);
+        tree.insert("a".chars(), "x".repeat(1000));
+
+        assert_eq!(
+            tree.tree().get_exact_match("a".chars()),
+            Some(&vec![(b'x', 1000)])
+        );
+        assert_eq!(
+            tree.get_by_shortest_prefix("ab".chars()),
+            Some("x".repeat(1000))
+        );
+        assert_eq!(
+            tree.insert("a".chars(), "yz".to_owned()),
+            Some("x".repeat(1000))
+        );
+        assert_eq!(tree.remove_exact_match("a".chars()), Some("yz".to_owned()));
+        assert_eq!(tree.get_exact_match("a".chars()), None);
+    }
+}
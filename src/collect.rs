@@ -0,0 +1,92 @@
+use std::hash::{BuildHasher, Hash};
+
+use crate::{IntoKeyItem, PrefixTree};
+
+impl<K: Hash + Eq, V, S: IntoIterator, H: BuildHasher + Clone + Default> FromIterator<(S, V)>
+    for PrefixTree<K, V, H>
+where
+    S::Item: IntoKeyItem<K>,
+{
+    /// Inserts the pairs in order, so of the pairs with the same key the last one wins
+    fn from_iter<T: IntoIterator<Item = (S, V)>>(pairs: T) -> Self {
+        let mut tree = Self::default();
+        tree.extend(pairs);
+        tree
+    }
+}
+
+impl<K: Hash + Eq, V, S: IntoIterator, H: BuildHasher + Clone> Extend<(S, V)>
+    for PrefixTree<K, V, H>
+where
+    S::Item: IntoKeyItem<K>,
+{
+    fn extend<T: IntoIterator<Item = (S, V)>>(&mut self, pairs: T) {
+        for (key, value) in pairs {
+            self.insert(key, value);
+        }
+    }
+}
+
+/// Builds trees from iterators of `(key, value)` pairs, with different handling of the pairs that
+/// have the same key
+pub trait CollectPrefixTree<K: Hash + Eq, V>: Sized {
+    /// Same as `collect`: the last of the pairs with the same key wins
+    fn collect_prefix_tree(self) -> PrefixTree<K, V>;
+
+    /// Stores the last value of every key together with the number of pairs that had the key
+    fn collect_counting(self) -> PrefixTree<K, (V, usize)>;
+
+    /// Stores all the values of every key, in the order of the pairs
+    fn collect_multi(self) -> PrefixTree<K, Vec<V>>;
+}
+
+impl<K: Hash + Eq, V, S: IntoIterator, T: Iterator<Item = (S, V)>> CollectPrefixTree<K, V> for T
+where
+    S::Item: IntoKeyItem<K>,
+{
+    fn collect_prefix_tree(self) -> PrefixTree<K, V> {
+        self.collect()
+    }
+
+    fn collect_counting(self) -> PrefixTree<K, (V, usize)> {
+        let mut tree = PrefixTree::new();
+        for (key, value) in self {
+            let slot = tree.slot(key);
+            let count = slot.as_ref().map_or(0, |(_, count)| *count);
+            *slot = Some((value, count + 1));
+        }
+        tree
+    }
+
+    fn collect_multi(self) -> PrefixTree<K, Vec<V>> {
+        let mut tree = PrefixTree::new();
+        for (key, value) in self {
+            tree.slot(key).get_or_insert_with(Vec::new).push(value);
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect() {
+        let pairs = || vec![("ab", 1), ("a", 2), ("ab", 3)].into_iter();
+        let chars = || pairs().map(|(key, value)| (key.chars(), value));
+
+        let tree: PrefixTree<char, _> = chars().collect_prefix_tree();
+        assert_eq!(tree.get_exact_match("ab".chars()), Some(&3));
+        assert_eq!(tree, chars().collect());
+
+        let counted = chars().collect_counting();
+        assert_eq!(counted.get_exact_match("ab".chars()), Some(&(3, 2)));
+        assert_eq!(counted.get_exact_match("a".chars()), Some(&(2, 1)));
+
+        let mut multi = chars().collect_multi();
+        assert_eq!(multi.get_exact_match("ab".chars()), Some(&vec![1, 3]));
+        multi.extend([("a".chars(), vec![])]);
+        assert_eq!(multi.get_exact_match("a".chars()), Some(&vec![]));
+    }
+}
@@ -0,0 +1,76 @@
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+/// A configuration that can be overlaid with a more specific one
+pub trait Merge {
+    /// Overlays `other`, which takes precedence wherever both configure the same thing
+    fn merge(&mut self, other: &Self);
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Merge for HashMap<K, V> {
+    fn merge(&mut self, other: &Self) {
+        self.extend(other.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Merge for BTreeMap<K, V> {
+    fn merge(&mut self, other: &Self) {
+        self.extend(other.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+}
+
+impl<K: Hash + Eq, V: Merge + Clone, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns the effective configuration for the path: the values of all the prefixes of the
+    /// path, merged from the root (least specific) to the longest prefix (most specific), or
+    /// `None` if no prefixes were found
+    pub fn resolve_config<I: Borrow<K>>(&self, path: impl IntoIterator<Item = I>) -> Option<V> {
+        let mut path = path.into_iter();
+        let mut root = self;
+        let mut resolved: Option<V> = None;
+        loop {
+            if let Some(value) = &root.value {
+                match &mut resolved {
+                    Some(resolved) => resolved.merge(value),
+                    None => resolved = Some(value.clone()),
+                }
+            }
+            root = match path
+                .next()
+                .and_then(|item| root.subtrees.get(item.borrow()))
+            {
+                Some(subtree) => subtree,
+                None => return resolved,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maplit::btreemap;
+
+    #[test]
+    fn test_resolve_config() {
+        let mut tree: PrefixTree<String, _> = PrefixTree::new();
+        tree.insert([] as [&str; 0], btreemap! {"indent" => 4, "width" => 80});
+        tree.insert(["src"], btreemap! {"width" => 100});
+        tree.insert(["src", "gen.rs"], btreemap! {"indent" => 2});
+
+        assert_eq!(
+            tree.resolve_config(["src", "gen.rs"].map(String::from)),
+            Some(btreemap! {"indent" => 2, "width" => 100})
+        );
+        assert_eq!(
+            tree.resolve_config(["docs", "a.md"].map(String::from)),
+            Some(btreemap! {"indent" => 4, "width" => 80})
+        );
+        assert_eq!(
+            PrefixTree::<char, HashMap<u8, u8>>::new().resolve_config("a".chars()),
+            None
+        );
+    }
+}
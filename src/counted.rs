@@ -0,0 +1,387 @@
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+
+use crate::{IntoKeyItem, PrefixMapLike, PrefixMapRead};
+
+/// A prefix tree where every node knows how many values are stored under it (itself included),
+/// so `count_under_prefix` only walks the prefix, e.g. for showing the number of completions.
+/// The counts are kept up to date by `insert` and `remove_exact_match`, which is why the fields
+/// are private. The children are kept in `BTreeMap`s like in `OrderedPrefixTree`, so the
+/// queries by position (`rank`, `select`, `complete_page`) can go through them in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountedPrefixTree<K: Ord, V> {
+    value: Option<V>,
+    count: usize,
+    subtrees: BTreeMap<K, CountedPrefixTree<K, V>>,
+}
+
+impl<K: Ord, V> Default for CountedPrefixTree<K, V> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            count: 0,
+            subtrees: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord, V> CountedPrefixTree<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same as `PrefixTree::insert`
+    pub fn insert<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        value: V,
+    ) -> Option<V> {
+        insert(self, &mut sequence.into_iter(), value)
+    }
+
+    pub fn get_exact_match<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        self.subtree(sequence)?.value.as_ref()
+    }
+
+    pub fn get_exact_match_mut<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&mut V> {
+        let mut root = self;
+        for item in sequence {
+            root = root.subtrees.get_mut(item.borrow())?;
+        }
+        root.value.as_mut()
+    }
+
+    /// Same as `PrefixTree::get_by_shortest_prefix`
+    pub fn get_by_shortest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        let mut sequence = sequence.into_iter();
+        let mut root = self;
+        loop {
+            if let Some(value) = &root.value {
+                return Some(value);
+            }
+            root = root.subtrees.get(sequence.next()?.borrow())?;
+        }
+    }
+
+    /// Same as `PrefixTree::remove_exact_match`: removes the branches left without values
+    pub fn remove_exact_match<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<V> {
+        remove(self, &mut sequence.into_iter())
+    }
+
+    /// Returns the number of values whose keys start with the prefix (including the prefix
+    /// itself) without walking the subtree under it
+    pub fn count_under_prefix<I: Borrow<K>>(&self, prefix: impl IntoIterator<Item = I>) -> usize {
+        self.subtree(prefix).map_or(0, |subtree| subtree.count)
+    }
+
+    /// Returns the number of stored values
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    fn subtree<I: Borrow<K>>(&self, prefix: impl IntoIterator<Item = I>) -> Option<&Self> {
+        let mut root = self;
+        for item in prefix {
+            root = root.subtrees.get(item.borrow())?;
+        }
+        Some(root)
+    }
+}
+
+impl<K: Ord + Clone, V> CountedPrefixTree<K, V> {
+    /// Returns the number of keys that come before the sequence in lexicographic order (the
+    /// sequence doesn't have to be a key). Only the nodes on the path of the sequence are visited,
+    /// summing the counts of their children with smaller items
+    pub fn rank<I: Borrow<K>>(&self, sequence: impl IntoIterator<Item = I>) -> usize {
+        let mut root = self;
+        let mut rank = 0;
+        for item in sequence {
+            let item = item.borrow();
+            // The key of the node itself is a proper prefix of the sequence
+            rank += root.value.iter().count();
+            rank += root
+                .subtrees
+                .range(..item)
+                .map(|(_, subtree)| subtree.count)
+                .sum::<usize>();
+            root = match root.subtrees.get(item) {
+                Some(subtree) => subtree,
+                None => break,
+            };
+        }
+        rank
+    }
+
+    /// Returns the entry with the key at the index in lexicographic order (so `select(0)` is the
+    /// smallest key). The counts tell which child the entry is under, so only the path to the
+    /// entry is visited
+    pub fn select(&self, mut index: usize) -> Option<(Vec<K>, &V)> {
+        if index >= self.count {
+            return None;
+        }
+        let mut key = Vec::new();
+        let mut root = self;
+        loop {
+            if let Some(value) = &root.value {
+                if index == 0 {
+                    return Some((key, value));
+                }
+                index -= 1;
+            }
+            let (item, subtree) = root.subtrees.iter().find(|(_, subtree)| {
+                if index < subtree.count {
+                    true
+                } else {
+                    index -= subtree.count;
+                    false
+                }
+            })?;
+            key.push(item.clone());
+            root = subtree;
+        }
+    }
+
+    /// Same as `PrefixTree::heaviest_prefixes`, but the numbers are the stored counts, so the
+    /// walk stops at `depth` instead of visiting every node
+    pub fn heaviest_prefixes(&self, depth: usize, limit: usize) -> Vec<(Vec<K>, usize)> {
+        let mut prefixes = Vec::new();
+        let mut stack = vec![(Vec::new(), self)];
+        while let Some((path, root)) = stack.pop() {
+            if path.len() == depth {
+                if root.count > 0 {
+                    prefixes.push((path, root.count));
+                }
+                continue;
+            }
+            for (item, subtree) in &root.subtrees {
+                let mut path = path.clone();
+                path.push(item.clone());
+                stack.push((path, subtree));
+            }
+        }
+        prefixes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        prefixes.truncate(limit);
+        prefixes
+    }
+
+    /// Same as `PrefixTree::complete_page`, but a skipped subtree costs a single look at its count,
+    /// so only the nodes on the way to the page and the entries of the page are visited, going
+    /// through their children in the order they are stored in
+    pub fn complete_page<I: Borrow<K>>(
+        &self,
+        prefix: impl IntoIterator<Item = I>,
+        offset: usize,
+        limit: usize,
+    ) -> Vec<(Vec<K>, &V)> {
+        let mut path = Vec::new();
+        let root = self.subtree(
+            prefix
+                .into_iter()
+                .inspect(|item| path.push(item.borrow().clone())),
+        );
+        let mut page = Vec::new();
+        if let Some(root) = root {
+            collect_page(root, &mut path, &mut { offset }, limit, &mut page);
+        }
+        page
+    }
+}
+
+fn collect_page<'a, K: Ord + Clone, V>(
+    root: &'a CountedPrefixTree<K, V>,
+    path: &mut Vec<K>,
+    offset: &mut usize,
+    limit: usize,
+    page: &mut Vec<(Vec<K>, &'a V)>,
+) {
+    if let Some(value) = &root.value {
+        if *offset == 0 {
+            if page.len() < limit {
+                page.push((path.clone(), value));
+            }
+        } else {
+            *offset -= 1;
+        }
+    }
+    for (item, subtree) in &root.subtrees {
+        if page.len() == limit {
+            return;
+        }
+        if subtree.count <= *offset {
+            *offset -= subtree.count;
+            continue;
+        }
+        path.push(item.clone());
+        collect_page(subtree, path, offset, limit, page);
+        path.pop();
+    }
+}
+
+fn insert<K: Ord, V, I: IntoKeyItem<K>>(
+    root: &mut CountedPrefixTree<K, V>,
+    sequence: &mut impl Iterator<Item = I>,
+    value: V,
+) -> Option<V> {
+    let replaced = match sequence.next() {
+        Some(item) => {
+            let subtree = root.subtrees.entry(item.into_key_item()).or_default();
+            insert(subtree, sequence, value)
+        }
+        None => root.value.replace(value),
+    };
+    if replaced.is_none() {
+        root.count += 1;
+    }
+    replaced
+}
+
+fn remove<K: Ord, V, I: Borrow<K>>(
+    root: &mut CountedPrefixTree<K, V>,
+    sequence: &mut impl Iterator<Item = I>,
+) -> Option<V> {
+    let removed = match sequence.next() {
+        Some(item) => {
+            let subtree = root.subtrees.get_mut(item.borrow())?;
+            let removed = remove(subtree, sequence);
+            if subtree.count == 0 {
+                root.subtrees.remove(item.borrow());
+            }
+            removed
+        }
+        None => root.value.take(),
+    };
+    if removed.is_some() {
+        root.count -= 1;
+    }
+    removed
+}
+
+impl<K: Ord, V> PrefixMapRead<K, V> for CountedPrefixTree<K, V> {
+    fn get_exact_match(&self, key: &[K]) -> Option<&V> {
+        CountedPrefixTree::get_exact_match(self, key)
+    }
+
+    fn get_by_shortest_prefix(&self, key: &[K]) -> Option<&V> {
+        CountedPrefixTree::get_by_shortest_prefix(self, key)
+    }
+
+    fn len(&self) -> usize {
+        CountedPrefixTree::len(self)
+    }
+}
+
+impl<K: Ord + Clone, V> PrefixMapLike<K, V> for CountedPrefixTree<K, V> {
+    fn insert(&mut self, key: &[K], value: V) -> Option<V> {
+        CountedPrefixTree::insert(self, key, value)
+    }
+
+    fn get_exact_match_mut(&mut self, key: &[K]) -> Option<&mut V> {
+        CountedPrefixTree::get_exact_match_mut(self, key)
+    }
+
+    fn remove_exact_match(&mut self, key: &[K]) -> Option<V> {
+        CountedPrefixTree::remove_exact_match(self, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_under_prefix() {
+        let mut tree = CountedPrefixTree::new();
+        for word in ["car", "cart", "care", "cat", "dog"] {
+            assert_eq!(tree.insert(word.chars(), ()), None);
+        }
+        assert_eq!(tree.insert("cat".chars(), ()), Some(()));
+
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.count_under_prefix("ca".chars()), 4);
+        assert_eq!(tree.count_under_prefix("car".chars()), 3);
+        assert_eq!(tree.count_under_prefix("x".chars()), 0);
+
+        assert_eq!(tree.remove_exact_match("car".chars()), Some(()));
+        assert_eq!(tree.remove_exact_match("car".chars()), None);
+        assert_eq!(tree.remove_exact_match("cartwheel".chars()), None);
+        assert_eq!(tree.count_under_prefix("car".chars()), 2);
+        tree.remove_exact_match("dog".chars());
+        assert!(!tree.subtrees.contains_key(&'d'));
+        assert_eq!(tree.get_by_shortest_prefix("cart".chars()), Some(&()));
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn test_rank_select() {
+        let mut tree = CountedPrefixTree::new();
+        let words = ["", "a", "ab", "abc", "b", "ba", "c"];
+        for (index, word) in words.into_iter().enumerate() {
+            tree.insert(word.chars(), index);
+        }
+
+        for (index, word) in words.into_iter().enumerate() {
+            assert_eq!(tree.rank(word.chars()), index);
+            let (key, &value) = tree.select(index).unwrap();
+            assert_eq!(
+                (key.into_iter().collect::<String>(), value),
+                (word.to_owned(), index)
+            );
+        }
+        assert_eq!(tree.rank("abb".chars()), 3);
+        assert_eq!(tree.rank("bz".chars()), 6);
+        assert_eq!(tree.rank("z".chars()), 7);
+        assert_eq!(tree.select(7), None);
+    }
+
+    #[test]
+    fn test_heaviest_prefixes() {
+        let mut tree = CountedPrefixTree::new();
+        for key in ["aa", "ab", "ac", "b", "ba", "bb", "c", "d"] {
+            tree.insert(key.chars(), ());
+        }
+
+        assert_eq!(
+            tree.heaviest_prefixes(1, 3),
+            vec![(vec!['a'], 3), (vec!['b'], 3), (vec!['c'], 1)]
+        );
+        assert_eq!(tree.heaviest_prefixes(0, 1), vec![(vec![], 8)]);
+        assert_eq!(tree.heaviest_prefixes(5, 1), vec![]);
+    }
+
+    #[test]
+    fn test_complete_page() {
+        let mut tree = CountedPrefixTree::new();
+        for (index, key) in ["xa", "xab", "xb", "xba", "xbb", "y"]
+            .into_iter()
+            .enumerate()
+        {
+            tree.insert(key.chars(), index);
+        }
+
+        let page: Vec<String> = tree
+            .complete_page("x".chars(), 2, 2)
+            .into_iter()
+            .map(|(key, _)| key.into_iter().collect())
+            .collect();
+        assert_eq!(page, ["xb", "xba"]);
+        assert_eq!(tree.complete_page("".chars(), 4, 5).len(), 2);
+        assert_eq!(tree.complete_page("x".chars(), 5, 2), []);
+        assert_eq!(tree.complete_page("z".chars(), 0, 2), []);
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+/// A walk down a tree that is fed one key item at a time and remembers the last node with a value
+/// it went through, so a maximal-munch lexer can overshoot and then retreat to the longest match
+#[derive(Debug)]
+pub struct Cursor<'a, K: Hash + Eq, V, S = RandomState> {
+    root: &'a PrefixTree<K, V, S>,
+    /// The current node, or `None` once an item led out of the tree
+    node: Option<&'a PrefixTree<K, V, S>>,
+    consumed: usize,
+    /// The number of items consumed when reaching the last node with a value, and that node
+    last_match: Option<(usize, &'a PrefixTree<K, V, S>)>,
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns a cursor at the root of the tree
+    pub fn cursor(&self) -> Cursor<'_, K, V, S> {
+        Cursor {
+            root: self,
+            node: Some(self),
+            consumed: 0,
+            last_match: self.value.as_ref().map(|_| (0, self)),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Finds the longest match starting at every position of the input that fits into `window`
+    /// items from its start; returns the start position, the length and the value of every match
+    /// in the order of the positions. Every position is walked separately, up to `window` items or
+    /// until the walk leaves the tree
+    pub fn scan_windows(&self, input: &[K], window: usize) -> Vec<(usize, usize, &V)> {
+        let mut matches = Vec::new();
+        for start in 0..input.len() {
+            let mut cursor = self.cursor();
+            let end = input.len().min(start + window);
+            for item in &input[start..end] {
+                if !cursor.advance(item) {
+                    break;
+                }
+            }
+            if let Some((len, value)) = cursor.last_match() {
+                matches.push((start, len, value));
+            }
+        }
+        matches
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher + Clone> Cursor<'a, K, V, S> {
+    /// Moves the cursor along the item; returns `false` if the items fed so far don't form a
+    /// prefix of any key (the cursor then ignores the items fed until it is rolled back)
+    pub fn advance(&mut self, item: &K) -> bool {
+        self.node = self.node.and_then(|node| node.subtrees.get(item));
+        if let Some(node) = self.node {
+            self.consumed += 1;
+            if node.value.is_some() {
+                self.last_match = Some((self.consumed, node));
+            }
+        }
+        self.node.is_some()
+    }
+
+    /// Returns the number of items consumed on the way to the last value found and that value
+    pub fn last_match(&self) -> Option<(usize, &'a V)> {
+        let (consumed, node) = self.last_match?;
+        Some((consumed, node.value.as_ref()?))
+    }
+
+    /// Moves the cursor back to the last value found and returns it (see `last_match`); the items
+    /// fed after it have to be fed again. Returns `None` and leaves the cursor as it is if no value
+    /// was found
+    pub fn rollback_to_last_match(&mut self) -> Option<(usize, &'a V)> {
+        let (consumed, node) = self.last_match?;
+        self.node = Some(node);
+        self.consumed = consumed;
+        self.last_match()
+    }
+
+    /// Returns the value at exactly the items fed so far
+    pub fn current_value(&self) -> Option<&'a V> {
+        self.node?.value.as_ref()
+    }
+
+    /// Moves the cursor back to the root, forgetting all the items fed, e.g. to start matching
+    /// the next token
+    pub fn reset(&mut self) {
+        *self = self.root.cursor();
+    }
+
+    /// Returns the number of items that led the cursor to its current node
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_rollback() {
+        let mut tokens = PrefixTree::new();
+        tokens.insert("=".chars(), "assign");
+        tokens.insert("==".chars(), "equals");
+        tokens.insert("===".chars(), "identical");
+        tokens.insert("=>>".chars(), "unused");
+
+        let input: Vec<char> = "==>".chars().collect();
+        let mut cursor = tokens.cursor();
+        assert_eq!(cursor.last_match(), None);
+        assert!(cursor.advance(&input[0]));
+        assert!(cursor.advance(&input[1]));
+        assert!(!cursor.advance(&input[2]));
+        assert_eq!(cursor.rollback_to_last_match(), Some((2, &"equals")));
+        assert_eq!(cursor.consumed(), 2);
+        assert!(cursor.advance(&'='));
+        assert_eq!(cursor.last_match(), Some((3, &"identical")));
+    }
+
+    #[test]
+    fn test_cursor_reset() {
+        let mut tokens = PrefixTree::new();
+        tokens.insert("if".chars(), "if");
+        tokens.insert("i".chars(), "name");
+
+        let mut cursor = tokens.cursor();
+        assert!(cursor.advance(&'i'));
+        assert_eq!(cursor.current_value(), Some(&"name"));
+        assert!(cursor.advance(&'f'));
+        assert_eq!(cursor.current_value(), Some(&"if"));
+        assert!(!cursor.advance(&'x'));
+        assert_eq!(cursor.current_value(), None);
+        cursor.reset();
+        assert_eq!((cursor.consumed(), cursor.last_match()), (0, None));
+        assert!(cursor.advance(&'i'));
+    }
+
+    #[test]
+    fn test_scan_windows() {
+        let mut patterns = PrefixTree::new();
+        patterns.insert("err".chars(), 1);
+        patterns.insert("error".chars(), 2);
+        patterns.insert("r".chars(), 3);
+
+        let input: Vec<char> = "errors".chars().collect();
+        assert_eq!(
+            patterns.scan_windows(&input, 4),
+            vec![(0, 3, &1), (1, 1, &3), (2, 1, &3), (4, 1, &3)]
+        );
+        assert_eq!(patterns.scan_windows(&input, 5)[0], (0, 5, &2));
+    }
+}
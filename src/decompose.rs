@@ -0,0 +1,208 @@
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Splits the whole sequence into consecutive non-empty keys stored in the tree and returns
+    /// them with their values (or `None` if the sequence can't be split that way), e.g. splits
+    /// `"sunflowerseed"` into `"sunflower"` and `"seed"`. The value at the empty key is never used
+    pub fn decompose(&self, sequence: &[K]) -> Option<Vec<(Vec<K>, &V)>> {
+        // `reached[end]` is the start and the value of a key that ends at `end` and starts at a
+        // position which is reachable itself
+        let mut reached: Vec<Option<(usize, &V)>> = vec![None; sequence.len() + 1];
+        for start in 0..sequence.len() {
+            if start > 0 && reached[start].is_none() {
+                continue;
+            }
+            for (end, value) in self.matches_from(sequence, start) {
+                reached[end].get_or_insert((start, value));
+            }
+        }
+        let mut parts = Vec::new();
+        let mut end = sequence.len();
+        while end > 0 {
+            let (start, value) = reached[end]?;
+            parts.push((sequence[start..end].to_vec(), value));
+            end = start;
+        }
+        parts.reverse();
+        Some(parts)
+    }
+
+    /// Returns up to `limit` distinct ways to split the whole sequence into consecutive non-empty
+    /// keys stored in the tree (see `decompose`), preferring shorter first parts. Branches that
+    /// can't be completed are never explored, so the time to find every split doesn't depend on
+    /// the number of dead ends
+    pub fn decompositions(&self, sequence: &[K], limit: usize) -> Vec<Vec<(Vec<K>, &V)>> {
+        let matches: Vec<_> = (0..sequence.len())
+            .map(|start| self.matches_from(sequence, start))
+            .collect();
+        // `completes[start]` is whether the sequence can be split from `start` to its end
+        let mut completes = vec![false; sequence.len() + 1];
+        completes[sequence.len()] = true;
+        for start in (0..sequence.len()).rev() {
+            completes[start] = matches[start].iter().any(|(end, _)| completes[*end]);
+        }
+        let mut splits = Vec::new();
+        let search = Search {
+            sequence,
+            matches: &matches,
+            completes: &completes,
+            limit,
+        };
+        search.collect(&mut splits);
+        splits
+    }
+
+    /// Same as `decompose`, but returns the split into the fewest parts
+    pub fn decompose_fewest(&self, sequence: &[K]) -> Option<Vec<(Vec<K>, &V)>> {
+        // `fewest[end]` is the number of parts in the best split found of the sequence up to
+        // `end`, along with the start and the value of the last part
+        let mut fewest: Vec<Option<(usize, usize, &V)>> = vec![None; sequence.len() + 1];
+        for start in 0..sequence.len() {
+            let parts = match fewest[start] {
+                Some((parts, ..)) => parts,
+                None if start == 0 => 0,
+                None => continue,
+            };
+            for (end, value) in self.matches_from(sequence, start) {
+                if fewest[end].map_or(true, |(best, ..)| parts + 1 < best) {
+                    fewest[end] = Some((parts + 1, start, value));
+                }
+            }
+        }
+        let mut parts = Vec::new();
+        let mut end = sequence.len();
+        while end > 0 {
+            let (_, start, value) = fewest[end]?;
+            parts.push((sequence[start..end].to_vec(), value));
+            end = start;
+        }
+        parts.reverse();
+        Some(parts)
+    }
+
+    /// Returns the end positions and values of the non-empty keys that occur in the sequence
+    /// starting at `start`, shortest first
+    pub(crate) fn matches_from(&self, sequence: &[K], start: usize) -> Vec<(usize, &V)> {
+        let mut matches = Vec::new();
+        let mut root = self;
+        for (end, item) in (start + 1..).zip(&sequence[start..]) {
+            root = match root.subtrees.get(item) {
+                Some(subtree) => subtree,
+                None => break,
+            };
+            if let Some(value) = &root.value {
+                matches.push((end, value));
+            }
+        }
+        matches
+    }
+}
+
+struct Search<'s, 'a, K, V> {
+    sequence: &'s [K],
+    /// The ends and values of the keys starting at every position
+    matches: &'s [Vec<(usize, &'a V)>],
+    completes: &'s [bool],
+    limit: usize,
+}
+
+impl<'s, 'a, K: Clone, V> Search<'s, 'a, K, V> {
+    /// Walks the splits depth-first with an explicit stack, since a split can have as many parts
+    /// as the sequence has items
+    fn collect(&self, splits: &mut Vec<Vec<(Vec<K>, &'a V)>>) {
+        // Every frame is the start of a part and the index of the next match to try from there;
+        // `parts` holds the parts leading to the top frame
+        let mut stack = vec![(0, 0)];
+        let mut parts: Vec<(usize, usize, &'a V)> = Vec::new();
+        while let Some(frame) = stack.last_mut() {
+            if splits.len() == self.limit {
+                return;
+            }
+            let (start, next) = *frame;
+            if start == self.sequence.len() {
+                let split = parts
+                    .iter()
+                    .map(|&(start, end, value)| (self.sequence[start..end].to_vec(), value))
+                    .collect();
+                splits.push(split);
+            } else if let Some(&(end, value)) = self.matches[start].get(next) {
+                frame.1 += 1;
+                if self.completes[end] {
+                    parts.push((start, end, value));
+                    stack.push((end, 0));
+                }
+                continue;
+            }
+            stack.pop();
+            parts.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(words: &[&str]) -> PrefixTree<char, usize> {
+        let mut tree = PrefixTree::new();
+        for (index, word) in words.iter().enumerate() {
+            tree.insert(word.chars(), index);
+        }
+        tree
+    }
+
+    fn split(tree: &PrefixTree<char, usize>, text: &str) -> Option<Vec<String>> {
+        let text: Vec<char> = text.chars().collect();
+        let parts = tree.decompose(&text)?;
+        Some(
+            parts
+                .into_iter()
+                .map(|(key, _)| key.into_iter().collect())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_decompose() {
+        let tree = words(&["sun", "sunflower", "flow", "seed", "flowers", "eed"]);
+        assert_eq!(
+            split(&tree, "sunflowerseed"),
+            Some(vec!["sunflower".to_owned(), "seed".to_owned()])
+        );
+        assert_eq!(split(&tree, "sunflowerseeds"), None);
+        assert_eq!(split(&tree, ""), Some(vec![]));
+    }
+
+    #[test]
+    fn test_decompositions() {
+        let tree = words(&["a", "ab", "b", "abc", "c", "bc"]);
+        let text: Vec<char> = "abc".chars().collect();
+        let joined = |splits: Vec<Vec<(Vec<char>, &usize)>>| -> Vec<String> {
+            splits
+                .into_iter()
+                .map(|split| {
+                    let parts: Vec<String> = split
+                        .into_iter()
+                        .map(|(key, _)| key.into_iter().collect())
+                        .collect();
+                    parts.join("+")
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            joined(tree.decompositions(&text, 10)),
+            ["a+b+c", "a+bc", "ab+c", "abc"]
+        );
+        assert_eq!(joined(tree.decompositions(&text, 2)), ["a+b+c", "a+bc"]);
+        assert_eq!(tree.decompositions(&['x'], 10).len(), 0);
+        assert_eq!(tree.decompose_fewest(&text).unwrap().len(), 1);
+        assert_eq!(tree.decompose_fewest(&['c', 'x']), None);
+
+        let long = vec!['a'; 100_000];
+        let splits = tree.decompositions(&long, 1);
+        assert_eq!(splits[0].len(), long.len());
+    }
+}
@@ -0,0 +1,112 @@
+use std::borrow::Borrow;
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use crate::{IntoKeyItem, PrefixTree};
+
+/// A set of the last `capacity` distinct sequences seen, for dropping repeated log lines and the
+/// like. Seeing a remembered sequence again makes it the most recent one; when a new sequence
+/// doesn't fit, the least recently seen one is forgotten
+#[derive(Debug)]
+pub struct DedupWindow<K: Hash + Eq> {
+    /// The time every remembered sequence was last seen at
+    tree: PrefixTree<K, u64>,
+    /// The sequences in the order they were seen, with the times; a sequence seen again has a
+    /// stale entry for every earlier time
+    order: VecDeque<(Vec<K>, u64)>,
+    capacity: usize,
+    len: usize,
+    time: u64,
+}
+
+impl<K: Hash + Eq + Clone> DedupWindow<K> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            order: VecDeque::new(),
+            capacity,
+            len: 0,
+            time: 0,
+        }
+    }
+
+    /// Records the sequence as seen; returns `true` if it was among the remembered ones
+    pub fn observe<I: IntoKeyItem<K>>(&mut self, sequence: impl IntoIterator<Item = I>) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+        let key: Vec<K> = sequence
+            .into_iter()
+            .map(IntoKeyItem::into_key_item)
+            .collect();
+        self.time += 1;
+        let seen = self.tree.insert(&key, self.time).is_some();
+        self.order.push_back((key, self.time));
+        if !seen {
+            self.len += 1;
+            if self.len > self.capacity {
+                self.evict();
+            }
+        }
+        if self.order.len() > 2 * self.capacity {
+            self.drop_stale();
+        }
+        seen
+    }
+
+    /// Returns `true` if the sequence is among the remembered ones, without recording it
+    pub fn contains<I: Borrow<K>>(&self, sequence: impl IntoIterator<Item = I>) -> bool {
+        self.tree.contains_exact(sequence)
+    }
+
+    /// Returns the number of remembered sequences
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Forgets the least recently seen sequence
+    fn evict(&mut self) {
+        while let Some((key, time)) = self.order.pop_front() {
+            if self.tree.get_exact_match(&key) == Some(&time) {
+                self.tree.remove_exact_match(&key);
+                self.len -= 1;
+                return;
+            }
+        }
+    }
+
+    /// Removes the stale entries, keeping the memory used proportional to the capacity
+    fn drop_stale(&mut self) {
+        let tree = &self.tree;
+        self.order
+            .retain(|(key, time)| tree.get_exact_match(key) == Some(time));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_window() {
+        let mut window = DedupWindow::new(2);
+        assert!(!window.observe("a".chars()));
+        assert!(!window.observe("b".chars()));
+        assert!(window.observe("a".chars()));
+        // "b" is the least recently seen one now
+        assert!(!window.observe("c".chars()));
+        assert!(!window.contains("b".chars()));
+        assert!(window.contains("a".chars()));
+        for _ in 0..10 {
+            assert!(window.observe("c".chars()));
+        }
+        assert!(window.order.len() <= 4);
+        assert!(window.observe("a".chars()));
+        assert_eq!(window.len(), 2);
+        assert!(!DedupWindow::new(0).observe("a".chars()));
+    }
+}
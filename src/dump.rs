@@ -0,0 +1,136 @@
+use std::fmt::{Debug, Write};
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+impl<K: Hash + Eq + Ord + Debug, V: Debug, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns a pretty-printed JSON summary of the tree for logging, showing at most
+    /// `max_children` children of every node (in the order of their keys) and nothing deeper than
+    /// `max_depth`. Keys and values are written with their `Debug` representation. Every node
+    /// whose children were cut off reports how many children (`elided_children`) and entries
+    /// (`elided_entries`) were left out:
+    ///
+    /// ```text
+    /// {
+    ///   "value": null,
+    ///   "children": {
+    ///     "'a'": {
+    ///       "value": "1",
+    ///       "elided_children": 1,
+    ///       "elided_entries": 2
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    pub fn debug_dump(&self, max_depth: usize, max_children: usize) -> String {
+        let mut output = String::new();
+        dump_node(self, max_depth, max_children, 0, &mut output);
+        output.push('\n');
+        output
+    }
+}
+
+fn dump_node<K: Hash + Eq + Ord + Debug, V: Debug, S: BuildHasher + Clone>(
+    root: &PrefixTree<K, V, S>,
+    max_depth: usize,
+    max_children: usize,
+    depth: usize,
+    output: &mut String,
+) {
+    let indent = "  ".repeat(2 * depth + 1);
+    output.push_str("{\n");
+    output.push_str(&indent);
+    output.push_str("\"value\": ");
+    match &root.value {
+        Some(value) => write_json_string(&format!("{:?}", value), output),
+        None => output.push_str("null"),
+    }
+    let children = root.sorted_subtrees();
+    let shown = if depth < max_depth {
+        children.len().min(max_children)
+    } else {
+        0
+    };
+    if shown > 0 {
+        output.push_str(",\n");
+        output.push_str(&indent);
+        output.push_str("\"children\": {\n");
+        for (i, (key, subtree)) in children[..shown].iter().enumerate() {
+            if i > 0 {
+                output.push_str(",\n");
+            }
+            output.push_str(&indent);
+            output.push_str("  ");
+            write_json_string(&format!("{:?}", key), output);
+            output.push_str(": ");
+            dump_node(subtree, max_depth, max_children, depth + 1, output);
+        }
+        output.push('\n');
+        output.push_str(&indent);
+        output.push('}');
+    }
+    let elided = &children[shown..];
+    if !elided.is_empty() {
+        let entries: usize = elided.iter().map(|(_, subtree)| subtree.len()).sum();
+        write!(
+            output,
+            ",\n{indent}\"elided_children\": {},\n{indent}\"elided_entries\": {}",
+            elided.len(),
+            entries,
+            indent = indent
+        )
+        .unwrap();
+    }
+    output.push('\n');
+    output.push_str(&indent[2..]);
+    output.push('}');
+}
+
+fn write_json_string(text: &str, output: &mut String) {
+    output.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            c if c.is_control() => write!(output, "\\u{:04x}", c as u32).unwrap(),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_dump() {
+        let mut tree = PrefixTree::new();
+        tree.insert("a".chars(), "1");
+        tree.insert("ab".chars(), "2");
+        tree.insert("abc".chars(), "3");
+        tree.insert("b".chars(), "\"4\"");
+        tree.insert("c".chars(), "5");
+
+        assert_eq!(
+            tree.debug_dump(1, 2),
+            r#"{
+  "value": null,
+  "children": {
+    "'a'": {
+      "value": "\"1\"",
+      "elided_children": 1,
+      "elided_entries": 2
+    },
+    "'b'": {
+      "value": "\"\\\"4\\\"\""
+    }
+  },
+  "elided_children": 1,
+  "elided_entries": 1
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+use crate::PrefixTree;
+
+/// An insertion rejected by a `FanoutLimitedTree`, since it would give the node at `depth` more
+/// children than allowed; the value is given back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FanoutExceeded<V> {
+    pub depth: usize,
+    pub value: V,
+}
+
+impl<V> Display for FanoutExceeded<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the node at depth {} has too many children", self.depth)
+    }
+}
+
+impl<V: Debug> Error for FanoutExceeded<V> {}
+
+/// A prefix tree where no node may have more than `max_fanout` children, bounding the size of
+/// every node's hash map when the keys come from an untrusted source. An insertion that would
+/// create one child too many is rejected as a whole, leaving the tree unchanged
+#[derive(Debug)]
+pub struct FanoutLimitedTree<K: Hash + Eq, V> {
+    tree: PrefixTree<K, V>,
+    max_fanout: usize,
+}
+
+impl<K: Hash + Eq + Clone, V> FanoutLimitedTree<K, V> {
+    pub fn new(max_fanout: usize) -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            max_fanout,
+        }
+    }
+
+    /// Same as `PrefixTree::insert`, unless a node on the way is full and would need a new child
+    pub fn insert(&mut self, key: &[K], value: V) -> Result<Option<V>, FanoutExceeded<V>> {
+        let mut root = &self.tree;
+        for (depth, item) in key.iter().enumerate() {
+            root = match root.subtrees.get(item) {
+                Some(subtree) => subtree,
+                None if root.subtrees.len() >= self.max_fanout => {
+                    return Err(FanoutExceeded { depth, value })
+                }
+                // The nodes below are new and get one child each, which fits any nonzero limit
+                None => break,
+            };
+        }
+        Ok(self.tree.insert(key, value))
+    }
+
+    pub fn get_exact_match(&self, key: &[K]) -> Option<&V> {
+        self.tree.get_exact_match(key)
+    }
+
+    pub fn get_by_shortest_prefix(&self, key: &[K]) -> Option<&V> {
+        self.tree.get_by_shortest_prefix(key)
+    }
+
+    pub fn remove_exact_match(&mut self, key: &[K]) -> Option<V> {
+        self.tree.remove_exact_match(key)
+    }
+
+    pub fn max_fanout(&self) -> usize {
+        self.max_fanout
+    }
+
+    pub fn tree(&self) -> &PrefixTree<K, V> {
+        &self.tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> Vec<char> {
+        key.chars().collect()
+    }
+
+    #[test]
+    fn test_fanout_limit() {
+        let mut tree = FanoutLimitedTree::new(2);
+        assert_eq!(tree.insert(&key("ab"), 1), Ok(None));
+        assert_eq!(tree.insert(&key("ac"), 2), Ok(None));
+        assert_eq!(
+            tree.insert(&key("ad"), 3),
+            Err(FanoutExceeded { depth: 1, value: 3 })
+        );
+        assert_eq!(tree.insert(&key("ab"), 4), Ok(Some(1)));
+        assert_eq!(tree.insert(&key("bcd"), 5), Ok(None));
+        assert_eq!(
+            tree.insert(&key("c"), 6),
+            Err(FanoutExceeded { depth: 0, value: 6 })
+        );
+        assert_eq!(tree.get_exact_match(&key("ad")), None);
+        assert_eq!(tree.tree().stats().max_fanout, 2);
+
+        assert_eq!(FanoutLimitedTree::new(0).insert(&key(""), 1), Ok(None));
+        assert!(FanoutLimitedTree::new(0).insert(&key("a"), 1).is_err());
+    }
+}
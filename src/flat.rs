@@ -0,0 +1,140 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::hash::BuildHasher;
+
+use crate::PrefixTree;
+
+/// How keys of trees with `String` key items are written as single strings: every segment is
+/// preceded by the separator, and the separator and the escape character are escaped inside the
+/// segments, so `["a/b", ""]` becomes `/a\/b/` and the empty key becomes an empty string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEscaping {
+    pub separator: char,
+    pub escape: char,
+}
+
+impl Default for KeyEscaping {
+    fn default() -> Self {
+        Self {
+            separator: '/',
+            escape: '\\',
+        }
+    }
+}
+
+impl KeyEscaping {
+    /// Writes the segments as a single string
+    pub fn join<S: AsRef<str>>(&self, segments: impl IntoIterator<Item = S>) -> String {
+        let mut joined = String::new();
+        for segment in segments {
+            joined.push(self.separator);
+            for c in segment.as_ref().chars() {
+                if c == self.separator || c == self.escape {
+                    joined.push(self.escape);
+                }
+                joined.push(c);
+            }
+        }
+        joined
+    }
+
+    /// Reads the segments back from a string written by `join`
+    pub fn split(&self, joined: &str) -> Result<Vec<String>, MalformedKey> {
+        let malformed = || MalformedKey(joined.to_owned());
+        let mut chars = joined.chars();
+        let mut segments = Vec::new();
+        match chars.next() {
+            None => return Ok(segments),
+            Some(c) if c == self.separator => segments.push(String::new()),
+            Some(_) => return Err(malformed()),
+        }
+        while let Some(c) = chars.next() {
+            let segment = segments.last_mut().unwrap();
+            if c == self.escape {
+                segment.push(chars.next().ok_or_else(malformed)?);
+            } else if c == self.separator {
+                segments.push(String::new());
+            } else {
+                segment.push(c);
+            }
+        }
+        Ok(segments)
+    }
+}
+
+/// A flat key that `KeyEscaping::split` couldn't read: it doesn't start with the separator or it
+/// ends with a lone escape character
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MalformedKey(pub String);
+
+impl Display for MalformedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "malformed flat key {:?}", self.0)
+    }
+}
+
+impl Error for MalformedKey {}
+
+impl<V, S: BuildHasher + Clone> PrefixTree<String, V, S> {
+    /// Returns all the entries of the tree with their keys joined into single strings, in
+    /// lexicographic order of the keys
+    pub fn export_flat(&self, escaping: KeyEscaping) -> Vec<(String, &V)> {
+        self.iter_sorted()
+            .map(|(key, value)| (escaping.join(key), value))
+            .collect()
+    }
+
+    /// Inserts entries with keys written by `export_flat`; stops at the first malformed key,
+    /// keeping the entries inserted before it
+    pub fn import_flat<T: AsRef<str>>(
+        &mut self,
+        entries: impl IntoIterator<Item = (T, V)>,
+        escaping: KeyEscaping,
+    ) -> Result<(), MalformedKey> {
+        for (key, value) in entries {
+            self.insert(escaping.split(key.as_ref())?, value);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_round_trip() {
+        let mut tree: PrefixTree<String, _> = PrefixTree::new();
+        tree.insert(["a/b", "c\\"], 1);
+        tree.insert([""], 2);
+        tree.insert([] as [&str; 0], 3);
+
+        let escaping = KeyEscaping::default();
+        let flat = tree.export_flat(escaping);
+        assert_eq!(
+            flat,
+            vec![
+                ("".to_owned(), &3),
+                ("/".to_owned(), &2),
+                ("/a\\/b/c\\\\".to_owned(), &1)
+            ]
+        );
+
+        let mut imported = PrefixTree::new();
+        imported
+            .import_flat(flat.into_iter().map(|(key, value)| (key, *value)), escaping)
+            .unwrap();
+        assert_eq!(imported, tree);
+
+        let dotted = KeyEscaping {
+            separator: '.',
+            escape: '~',
+        };
+        assert_eq!(
+            dotted.split(".a~.b.c"),
+            Ok(vec!["a.b".to_owned(), "c".to_owned()])
+        );
+        assert!(dotted.split("a.b").is_err());
+        assert!(dotted.split(".a~").is_err());
+    }
+}
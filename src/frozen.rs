@@ -0,0 +1,339 @@
+use std::borrow::Borrow;
+use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hash};
+use std::ops::Range;
+
+use crate::{PrefixMapRead, PrefixTree};
+
+/// A read-only prefix tree packed into flat arrays, created by `PrefixTree::freeze`. The nodes are
+/// stored in breadth-first order, so the children of every node are adjacent and sorted by their
+/// keys, and a step down the tree is a binary search instead of a hash map lookup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrozenPrefixTree<K, V> {
+    /// The children of node `n` are the nodes `first_child[n]..first_child[n + 1]`; node 0 is the
+    /// root
+    first_child: Vec<usize>,
+    /// The last key item of every node except the root (so node `n` has the key item `n - 1`)
+    items: Vec<K>,
+    /// Which nodes have values
+    present: Presence,
+    /// The values in the order of their nodes (the value of node `n` is
+    /// `values[present.rank(n)]`), so membership and counting only read the bitmap
+    values: Vec<V>,
+}
+
+/// A bitmap of the nodes that have values, with the number of set bits before every word to
+/// count the set bits before any node in constant time
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct Presence {
+    words: Vec<u64>,
+    ranks: Vec<usize>,
+    count: usize,
+}
+
+impl<K: Hash + Eq + Ord, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Packs the tree into its read-only representation
+    pub fn freeze(self) -> FrozenPrefixTree<K, V> {
+        let mut frozen = FrozenPrefixTree {
+            first_child: Vec::new(),
+            items: Vec::new(),
+            present: Presence::default(),
+            values: Vec::new(),
+        };
+        let mut queue = VecDeque::from([self]);
+        let mut next = 1;
+        while let Some(node) = queue.pop_front() {
+            frozen
+                .present
+                .push(frozen.first_child.len(), node.value.is_some());
+            frozen.first_child.push(next);
+            frozen.values.extend(node.value);
+            let mut subtrees: Vec<_> = node.subtrees.into_iter().collect();
+            subtrees.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+            next += subtrees.len();
+            for (item, subtree) in subtrees {
+                frozen.items.push(item);
+                queue.push_back(subtree);
+            }
+        }
+        frozen.first_child.push(next);
+        frozen
+    }
+}
+
+impl<K: Ord, V> FrozenPrefixTree<K, V> {
+    /// Returns the value at exactly the given sequence
+    pub fn get_exact_match<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        let mut node = 0;
+        for item in sequence {
+            node = self.child(node, item.borrow())?;
+        }
+        self.value(node)
+    }
+
+    /// Same as `get_exact_match` for every query, in the order of the queries. The lookups go
+    /// down the tree together, one step of every lookup at a time, and every step prefetches the
+    /// node the lookup moves to, so the memory accesses of different lookups overlap instead of
+    /// waiting for each other
+    pub fn get_exact_matches<I: Borrow<K>, Q: IntoIterator<Item = I>>(
+        &self,
+        queries: impl IntoIterator<Item = Q>,
+    ) -> Vec<Option<&V>> {
+        let mut lookups: Vec<_> = queries
+            .into_iter()
+            .map(|query| (query.into_iter(), 0))
+            .collect();
+        let mut results = vec![None; lookups.len()];
+        let mut pending: Vec<usize> = (0..lookups.len()).collect();
+        while !pending.is_empty() {
+            pending.retain(|&index| {
+                let (sequence, node) = &mut lookups[index];
+                let item = match sequence.next() {
+                    Some(item) => item,
+                    None => {
+                        results[index] = self.value(*node);
+                        return false;
+                    }
+                };
+                match self.child(*node, item.borrow()) {
+                    Some(child) => {
+                        *node = child;
+                        prefetch(&self.first_child[child]);
+                        true
+                    }
+                    None => false,
+                }
+            });
+        }
+        results
+    }
+
+    /// Returns `true` if there is a value at exactly the given sequence, without reading the
+    /// values
+    pub fn contains_exact<I: Borrow<K>>(&self, sequence: impl IntoIterator<Item = I>) -> bool {
+        let mut node = 0;
+        for item in sequence {
+            node = match self.child(node, item.borrow()) {
+                Some(child) => child,
+                None => return false,
+            };
+        }
+        self.present.contains(node)
+    }
+
+    /// Returns the number of values whose keys start with the prefix, without reading the
+    /// values. The descendants of a node on every level are adjacent in the breadth-first order,
+    /// so every level takes two rank queries
+    pub fn count_under_prefix<I: Borrow<K>>(&self, prefix: impl IntoIterator<Item = I>) -> usize {
+        let mut node = 0;
+        for item in prefix {
+            node = match self.child(node, item.borrow()) {
+                Some(child) => child,
+                None => return 0,
+            };
+        }
+        let mut level = node..node + 1;
+        let mut count = 0;
+        while !level.is_empty() {
+            count += self.present.rank(level.end) - self.present.rank(level.start);
+            level = self.first_child[level.start]..self.first_child[level.end];
+        }
+        count
+    }
+
+    /// Returns the value associated with the shortest prefix of the given sequence
+    pub fn get_by_shortest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        let mut sequence = sequence.into_iter();
+        let mut node = 0;
+        loop {
+            if let Some(value) = self.value(node) {
+                return Some(value);
+            }
+            node = self.child(node, sequence.next()?.borrow())?;
+        }
+    }
+
+    /// Returns the value associated with the longest prefix of the given sequence
+    pub fn get_by_longest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        let mut node = 0;
+        let mut found = self.value(node);
+        for item in sequence {
+            node = match self.child(node, item.borrow()) {
+                Some(child) => child,
+                None => break,
+            };
+            found = self.value(node).or(found);
+        }
+        found
+    }
+
+    /// Returns the number of stored values
+    pub fn len(&self) -> usize {
+        self.present.count
+    }
+
+    /// Returns `true` if the tree stores no values
+    pub fn is_empty(&self) -> bool {
+        self.present.count == 0
+    }
+
+    fn value(&self, node: usize) -> Option<&V> {
+        if self.present.contains(node) {
+            Some(&self.values[self.present.rank(node)])
+        } else {
+            None
+        }
+    }
+
+    fn children(&self, node: usize) -> Range<usize> {
+        self.first_child[node]..self.first_child[node + 1]
+    }
+
+    fn child(&self, node: usize, item: &K) -> Option<usize> {
+        let children = self.children(node);
+        let items = &self.items[children.start - 1..children.end - 1];
+        let index = items.binary_search(item).ok()?;
+        Some(children.start + index)
+    }
+}
+
+impl<K: Hash + Eq, V> FrozenPrefixTree<K, V> {
+    /// Unpacks the tree back into a mutable one
+    pub fn thaw(self) -> PrefixTree<K, V> {
+        let count = self.first_child.len() - 1;
+        // `nodes[count - 1 - n]` is node `n`: building the nodes from the last one, all the
+        // children of a node are built before it
+        let mut nodes: Vec<Option<PrefixTree<K, V>>> = Vec::with_capacity(count);
+        let mut items: Vec<Option<K>> = self.items.into_iter().map(Some).collect();
+        let mut values = self.values;
+        for node in (0..count).rev() {
+            let mut tree = PrefixTree::new();
+            if self.present.contains(node) {
+                tree.value = values.pop();
+            }
+            for child in self.first_child[node]..self.first_child[node + 1] {
+                let subtree = nodes[count - 1 - child].take().unwrap();
+                tree.subtrees
+                    .insert(items[child - 1].take().unwrap(), subtree);
+            }
+            nodes.push(Some(tree));
+        }
+        nodes.pop().flatten().unwrap_or_default()
+    }
+}
+
+/// Hints the processor to start loading the memory at the reference
+fn prefetch<T>(target: &T) {
+    #[cfg(target_arch = "x86_64")]
+    // SAFETY: a prefetch is only a hint, it never faults
+    unsafe {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(target as *const T as *const i8);
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = target;
+}
+
+impl Presence {
+    /// Appends the bit of the next node
+    fn push(&mut self, node: usize, bit: bool) {
+        if node % 64 == 0 {
+            self.words.push(0);
+            self.ranks.push(self.count);
+        }
+        if bit {
+            *self.words.last_mut().unwrap() |= 1 << (node % 64);
+            self.count += 1;
+        }
+    }
+
+    fn contains(&self, node: usize) -> bool {
+        self.words[node / 64] & (1 << (node % 64)) != 0
+    }
+
+    /// Returns the number of nodes before the given one that have values
+    fn rank(&self, node: usize) -> usize {
+        match self.words.get(node / 64) {
+            Some(word) => {
+                let below = word & ((1 << (node % 64)) - 1);
+                self.ranks[node / 64] + below.count_ones() as usize
+            }
+            None => self.count,
+        }
+    }
+}
+
+impl<K: Ord, V> PrefixMapRead<K, V> for FrozenPrefixTree<K, V> {
+    fn get_exact_match(&self, key: &[K]) -> Option<&V> {
+        FrozenPrefixTree::get_exact_match(self, key)
+    }
+
+    fn get_by_shortest_prefix(&self, key: &[K]) -> Option<&V> {
+        FrozenPrefixTree::get_by_shortest_prefix(self, key)
+    }
+
+    fn len(&self) -> usize {
+        FrozenPrefixTree::len(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presence_rank() {
+        let mut presence = Presence::default();
+        for node in 0..200 {
+            presence.push(node, node % 3 == 0);
+        }
+        for node in 0..=200 {
+            assert_eq!(presence.rank(node), (node + 2) / 3);
+        }
+        assert!(presence.contains(129));
+        assert!(!presence.contains(130));
+    }
+
+    #[test]
+    fn test_freeze() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["", "ab", "abc", "b", "ba", "bb"].into_iter().enumerate() {
+            tree.insert(key.chars(), index);
+        }
+        let copy = tree
+            .iter_sorted()
+            .map(|(key, &value)| (key, value))
+            .collect::<PrefixTree<_, _>>();
+
+        let frozen = tree.freeze();
+        assert_eq!(frozen.get_exact_match("abc".chars()), Some(&2));
+        assert_eq!(frozen.get_exact_match("a".chars()), None);
+        assert_eq!(frozen.get_exact_match("bc".chars()), None);
+        assert_eq!(frozen.get_by_shortest_prefix("bb".chars()), Some(&0));
+        assert_eq!(frozen.get_by_longest_prefix("abx".chars()), Some(&1));
+        assert_eq!(frozen.len(), 6);
+        assert!(frozen.contains_exact("ba".chars()));
+        assert!(!frozen.contains_exact("a".chars()));
+        assert_eq!(frozen.count_under_prefix("".chars()), 6);
+        assert_eq!(frozen.count_under_prefix("b".chars()), 3);
+        assert_eq!(frozen.count_under_prefix("a".chars()), 2);
+        assert_eq!(frozen.count_under_prefix("c".chars()), 0);
+        let queries = ["abc", "a", "", "bcd", "ba"];
+        let expected: Vec<_> = queries
+            .iter()
+            .map(|query| frozen.get_exact_match(query.chars()))
+            .collect();
+        let batch = frozen.get_exact_matches(queries.iter().map(|query| query.chars()));
+        assert_eq!(batch, expected);
+        assert_eq!(frozen.thaw(), copy);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::testing::Oracle;
+use crate::Op;
+
+/// The keys are drawn from a small alphabet and kept short, so that random operations hit the
+/// same branches often
+const ALPHABET: u8 = 4;
+const MAX_KEY_LEN: u8 = 6;
+
+/// Decodes a sequence of operations from arbitrary bytes; every input decodes to some (possibly
+/// empty) sequence
+pub fn ops_from_bytes(data: &[u8]) -> Vec<Op<u8, u8>> {
+    let mut bytes = data.iter().copied();
+    let mut ops = Vec::new();
+    while let (Some(kind), Some(len)) = (bytes.next(), bytes.next()) {
+        let key: Vec<u8> = (&mut bytes)
+            .take(usize::from(len % (MAX_KEY_LEN + 1)))
+            .map(|item| item % ALPHABET)
+            .collect();
+        ops.push(match kind % 3 {
+            0 => match bytes.next() {
+                Some(value) => Op::Insert(key, value),
+                None => break,
+            },
+            1 => Op::RemoveExactMatch(key),
+            _ => Op::RemoveByShortestPrefix(key),
+        });
+    }
+    ops
+}
+
+/// Applies the operations decoded from the bytes to a `testing::Oracle`, which panics as soon as
+/// the tree disagrees with the flat map of keys to values or is left with branches that hold no
+/// values. Meant to be called directly from a fuzz target, e.g.
+/// `fuzz_target!(|data: &[u8]| check_ops(data))`
+pub fn check_ops(data: &[u8]) {
+    let mut oracle = Oracle::new();
+    for op in ops_from_bytes(data) {
+        let key = match &op {
+            Op::Insert(key, _) | Op::RemoveExactMatch(key) | Op::RemoveByShortestPrefix(key) => {
+                key.clone()
+            }
+        };
+        oracle.apply(op);
+        oracle.get_exact_match(&key);
+        oracle.get_by_shortest_prefix(&key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_ops() {
+        let mut state = 0x2545_f491_u32;
+        let data: Vec<u8> = (0..4096)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        for chunk in data.chunks(256) {
+            check_ops(chunk);
+        }
+        assert_eq!(
+            ops_from_bytes(&[0, 2, 5, 6, 7, 1, 0]),
+            vec![Op::Insert(vec![1, 2], 7), Op::RemoveExactMatch(vec![])]
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use std::cmp::Reverse;
+
+use crate::{IntoKeyItem, PrefixTree};
+
+/// A prefix tree with `String` key items where the segments containing `*` (any run of
+/// characters) or `?` (a single character) are glob patterns, each matched against a single
+/// segment of the looked up path. A lookup finds the entry with the longest key matching a
+/// prefix of the path; among entries with keys of the same length, the one with the more specific
+/// segment wins, comparing the segments from the left: a literal segment beats a segment that only
+/// has `?`, which beats a segment with `*`, and among the patterns of the same kind the one with
+/// more literal characters wins, falling back to the lexicographic order
+#[derive(Debug)]
+pub struct GlobPrefixTree<V> {
+    tree: PrefixTree<String, V>,
+}
+
+impl<V> Default for GlobPrefixTree<V> {
+    fn default() -> Self {
+        Self {
+            tree: PrefixTree::new(),
+        }
+    }
+}
+
+impl<V> GlobPrefixTree<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same as `PrefixTree::insert`; a segment is stored as a pattern if it has `*` or `?` in it
+    pub fn insert<I: IntoKeyItem<String>>(
+        &mut self,
+        pattern: impl IntoIterator<Item = I>,
+        value: V,
+    ) -> Option<V> {
+        self.tree.insert(pattern, value)
+    }
+
+    /// Returns the value of the entry that matches the longest prefix of the path with the highest
+    /// precedence (or `None` if no entries match)
+    pub fn get<'a>(&self, path: impl IntoIterator<Item = &'a str>) -> Option<&V> {
+        let path: Vec<&str> = path.into_iter().collect();
+        let mut best = None;
+        find(&self.tree, &path, 0, &mut best);
+        best.map(|(_, value)| value)
+    }
+
+    /// Removes the entry registered with exactly this pattern
+    pub fn remove_exact_match<'a>(
+        &mut self,
+        pattern: impl IntoIterator<Item = &'a str>,
+    ) -> Option<V> {
+        self.tree
+            .remove_exact_match(pattern.into_iter().map(String::from))
+    }
+
+    pub fn tree(&self) -> &PrefixTree<String, V> {
+        &self.tree
+    }
+
+    /// Returns the keys of the entries that can never be found by `get` because another entry
+    /// with a key of the same length matches every path they match and wins over them, in
+    /// lexicographic order. The check is conservative: an entry is only reported if it is certainly
+    /// shadowed
+    pub fn shadowed_entries(&self) -> Vec<Vec<String>> {
+        let keys: Vec<Vec<String>> = self.tree.iter_sorted().map(|(key, _)| key).collect();
+        keys.iter()
+            .filter(|shadowed| {
+                keys.iter().any(|key| {
+                    key.len() == shadowed.len()
+                        && key != *shadowed
+                        && wins_over(key, shadowed)
+                        && key
+                            .iter()
+                            .zip(shadowed.iter())
+                            .all(|(pattern, segment)| glob_covers(pattern, segment))
+                })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Returns `true` if the first of two matching keys of the same length is the one `get` returns
+fn wins_over(key: &[String], other: &[String]) -> bool {
+    let key = key.iter().map(|segment| precedence(segment));
+    let other = other.iter().map(|segment| precedence(segment));
+    key.lt(other)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Char(char),
+    AnyChar,
+    AnyRun,
+}
+
+fn tokens(pattern: &str) -> Vec<Token> {
+    pattern
+        .chars()
+        .map(|c| match c {
+            '?' => Token::AnyChar,
+            '*' => Token::AnyRun,
+            c => Token::Char(c),
+        })
+        .collect()
+}
+
+/// Returns `true` if the glob pattern matches every text matched by the other pattern (a literal
+/// segment is a pattern matching only itself). May return `false` for some pairs of patterns
+/// that match the same texts written differently, like `?*` and `*?`
+fn glob_covers(pattern: &str, other: &str) -> bool {
+    let (pattern, other) = (tokens(pattern), tokens(other));
+    // `covers[i][j]` is whether `pattern[i..]` covers `other[j..]`
+    let mut covers = vec![vec![false; other.len() + 1]; pattern.len() + 1];
+    covers[pattern.len()][other.len()] = true;
+    for i in (0..pattern.len()).rev() {
+        for j in (0..=other.len()).rev() {
+            covers[i][j] = match (pattern[i], other.get(j)) {
+                (Token::AnyRun, next) => covers[i + 1][j] || (next.is_some() && covers[i][j + 1]),
+                (_, None) => false,
+                (Token::AnyChar, Some(next)) => *next != Token::AnyRun && covers[i + 1][j + 1],
+                (Token::Char(c), Some(next)) => *next == Token::Char(c) && covers[i + 1][j + 1],
+            };
+        }
+    }
+    covers[0][0]
+}
+
+pub(crate) fn is_pattern(segment: &str) -> bool {
+    segment.contains(['*', '?'])
+}
+
+/// Orders the segments by precedence, the most specific first
+pub(crate) fn precedence(segment: &str) -> (u8, Reverse<usize>, &str) {
+    let rank = if segment.contains('*') {
+        2
+    } else if segment.contains('?') {
+        1
+    } else {
+        0
+    };
+    let literal = segment.chars().filter(|&c| c != '*' && c != '?').count();
+    (rank, Reverse(literal), segment)
+}
+
+/// Returns `true` if the glob pattern matches the whole text
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The position of the last `*` and of the text it was tried at, to backtrack to
+    let mut star = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Visits the children of the node in precedence order, so the first match found at any depth is
+/// the one that wins at that depth
+pub(crate) fn matching_children<'a, 'b, V>(
+    root: &'a PrefixTree<String, V>,
+    segment: &'b str,
+) -> impl Iterator<Item = &'a PrefixTree<String, V>> + 'b
+where
+    'a: 'b,
+{
+    let mut patterns: Vec<_> = root
+        .subtrees
+        .iter()
+        .filter(|(key, _)| is_pattern(key) && glob_match(key, segment))
+        .collect();
+    patterns.sort_unstable_by_key(|(key, _)| precedence(key));
+    root.subtrees
+        .get(segment)
+        .into_iter()
+        .chain(patterns.into_iter().map(|(_, subtree)| subtree))
+}
+
+fn find<'a, V>(
+    root: &'a PrefixTree<String, V>,
+    path: &[&str],
+    depth: usize,
+    best: &mut Option<(usize, &'a V)>,
+) {
+    if let Some(value) = &root.value {
+        if best.map_or(true, |(best_depth, _)| depth > best_depth) {
+            *best = Some((depth, value));
+        }
+    }
+    if let Some((segment, rest)) = path.split_first() {
+        for subtree in matching_children(root, segment) {
+            find(subtree, rest, depth + 1, best);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_prefix_tree() {
+        assert!(glob_match("*.rs", "lib.rs"));
+        assert!(glob_match("a*b?c", "axxbyc"));
+        assert!(!glob_match("a?", "a"));
+        assert!(glob_match("**", ""));
+
+        let mut rules = GlobPrefixTree::new();
+        rules.insert(["target"], "ignore");
+        rules.insert(["src", "*.rs"], "source");
+        rules.insert(["src", "lib.rs"], "root");
+        rules.insert(["src", "?.rs"], "short");
+        rules.insert(["*", "tmp"], "temporary");
+
+        assert_eq!(rules.get(["src", "lib.rs"]), Some(&"root"));
+        assert_eq!(rules.get(["src", "a.rs"]), Some(&"short"));
+        assert_eq!(rules.get(["src", "main.rs"]), Some(&"source"));
+        assert_eq!(rules.get(["target", "debug"]), Some(&"ignore"));
+        assert_eq!(rules.get(["target", "tmp"]), Some(&"temporary"));
+        assert_eq!(rules.get(["src", "main.c"]), None);
+    }
+
+    #[test]
+    fn test_shadowed_entries() {
+        assert!(glob_covers("*", "a?b*"));
+        assert!(glob_covers("a*", "ab?"));
+        assert!(!glob_covers("a?", "a*"));
+
+        let mut rules = GlobPrefixTree::new();
+        rules.insert(["src", "a?"], 1);
+        rules.insert(["src", "a*"], 2);
+        rules.insert(["src", "*"], 3);
+        rules.insert(["src", "**"], 4);
+        rules.insert(["*", "x"], 5);
+        rules.insert(["src", "x"], 6);
+        assert_eq!(
+            rules.shadowed_entries(),
+            vec![vec!["src".to_owned(), "**".to_owned()]]
+        );
+        assert_eq!(rules.get(["src", "ab"]), Some(&1));
+        assert_eq!(rules.get(["src", "abc"]), Some(&2));
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasher, Hasher};
+
+/// A hasher for the children maps that is seeded explicitly instead of randomly, so two trees
+/// built with the same seed by the same sequence of operations store (and iterate) their children
+/// in the same order, even in different processes. The order may still change between Rust
+/// versions, as the standard hashing algorithm is not guaranteed to stay the same
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeededState {
+    seed: u64,
+}
+
+impl SeededState {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl BuildHasher for SeededState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.seed);
+        hasher
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Op, PrefixTree};
+
+    use super::*;
+
+    #[test]
+    fn test_seeded_state() {
+        let build = || {
+            let mut tree = PrefixTree::with_hasher(SeededState::new(7));
+            for key in ["ab", "ac", "ad", "b", "c", "d", "e", "f"] {
+                tree.insert(key.chars(), key);
+            }
+            tree
+        };
+        let order = |tree: &PrefixTree<char, &str, SeededState>| {
+            let children = &tree.subtrees[&'a'].subtrees;
+            let mut order: Vec<char> = tree.subtrees.keys().copied().collect();
+            order.extend(children.keys());
+            order
+        };
+        let (first, second) = (build(), build());
+        assert_eq!(order(&first), order(&second));
+        assert_eq!(first, second);
+        assert_eq!(first.subtrees[&'a'].subtrees.hasher().seed(), 7);
+    }
+
+    #[test]
+    fn test_seeded_tree_queries() {
+        let ops = ["cat", "car", "cart", "dog"]
+            .iter()
+            .enumerate()
+            .map(|(index, key)| Op::Insert(key.chars().collect(), index));
+        let tree = PrefixTree::replay_with_hasher(ops, SeededState::new(3));
+        let text: Vec<char> = "cart".chars().collect();
+
+        let near: Vec<_> = tree
+            .search_within_distance(&text, 1)
+            .into_iter()
+            .map(|(key, distance, _)| (key.into_iter().collect::<String>(), distance))
+            .collect();
+        assert_eq!(
+            near,
+            [
+                ("car".to_owned(), 1),
+                ("cart".to_owned(), 0),
+                ("cat".to_owned(), 1)
+            ]
+        );
+        assert_eq!(tree.find_matches(&text)[0], (0, 3, &1));
+        assert_eq!(tree.decompose(&text).unwrap().len(), 1);
+        assert_eq!(tree.top_completions("ca".chars(), 1)[0].1, &2);
+        assert_eq!(tree.stats().entries, 4);
+        assert_eq!(tree.get_exact_match_utf8(*b"dog"), Ok(Some(&3)));
+        assert_eq!(tree.freeze().get_exact_match("car".chars()), Some(&1));
+    }
+}
@@ -0,0 +1,192 @@
+use std::borrow::Borrow;
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use crate::{IntoKeyItem, PrefixTree};
+
+/// The value that a key should be set to (`None` meaning "removed") to revert a mutation
+#[derive(Debug)]
+struct Change<K, V> {
+    key: Vec<K>,
+    value: Option<V>,
+}
+
+/// A prefix tree that records the inverse of each of its mutations so they can be undone and
+/// redone; at most `max_depth` mutations are remembered, older ones are forgotten
+#[derive(Debug)]
+pub struct HistoryTree<K: Hash + Eq, V> {
+    tree: PrefixTree<K, V>,
+    undo: VecDeque<Change<K, V>>,
+    redo: Vec<Change<K, V>>,
+    max_depth: usize,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> HistoryTree<K, V> {
+    pub fn new(max_depth: usize) -> Self {
+        Self::from_tree(PrefixTree::new(), max_depth)
+    }
+
+    /// Wraps an existing tree; its current contents become the oldest state that can be restored
+    pub fn from_tree(tree: PrefixTree<K, V>, max_depth: usize) -> Self {
+        Self {
+            tree,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            max_depth,
+        }
+    }
+
+    pub fn tree(&self) -> &PrefixTree<K, V> {
+        &self.tree
+    }
+
+    pub fn into_tree(self) -> PrefixTree<K, V> {
+        self.tree
+    }
+
+    /// Same as `PrefixTree::insert`, but the mutation is recorded
+    pub fn insert<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        value: V,
+    ) -> Option<V> {
+        let key: Vec<K> = sequence
+            .into_iter()
+            .map(IntoKeyItem::into_key_item)
+            .collect();
+        let previous = self.tree.insert(&key, value);
+        self.record(key, previous.clone());
+        previous
+    }
+
+    /// Same as `PrefixTree::remove_exact_match`, but the mutation is recorded
+    pub fn remove_exact_match<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<V> {
+        let key: Vec<K> = sequence
+            .into_iter()
+            .map(|item| item.borrow().clone())
+            .collect();
+        let removed = self.tree.remove_exact_match(&key)?;
+        self.record(key, Some(removed.clone()));
+        Some(removed)
+    }
+
+    /// Same as `PrefixTree::remove_by_shortest_prefix`, but the mutation is recorded
+    pub fn remove_by_shortest_prefix<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<V> {
+        let mut sequence = sequence.into_iter();
+        let mut key = Vec::new();
+        let mut root = &self.tree;
+        while root.value.is_none() {
+            let item = sequence.next()?;
+            root = root.subtrees.get(item.borrow())?;
+            key.push(item.borrow().clone());
+        }
+        let removed = self.tree.remove_exact_match(&key)?;
+        self.record(key, Some(removed.clone()));
+        Some(removed)
+    }
+
+    /// Reverts the latest recorded mutation; returns `false` if there was nothing to undo
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop_back() {
+            Some(change) => {
+                let change = self.apply(change);
+                self.redo.push(change);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Reapplies the latest undone mutation; returns `false` if there was nothing to redo.
+    /// Any new mutation discards the mutations that could have been redone
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(change) => {
+                let change = self.apply(change);
+                self.push_undo(change);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forgets all recorded mutations without changing the tree
+    pub fn clear_history(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Applies the change and returns the change that reverts it
+    fn apply(&mut self, change: Change<K, V>) -> Change<K, V> {
+        let previous = match change.value {
+            Some(value) => self.tree.insert(&change.key, value),
+            None => self.tree.remove_exact_match(&change.key),
+        };
+        Change {
+            key: change.key,
+            value: previous,
+        }
+    }
+
+    fn record(&mut self, key: Vec<K>, previous: Option<V>) {
+        self.redo.clear();
+        self.push_undo(Change {
+            key,
+            value: previous,
+        });
+    }
+
+    fn push_undo(&mut self, change: Change<K, V>) {
+        if self.max_depth == 0 {
+            return;
+        }
+        if self.undo.len() == self.max_depth {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(change);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_tree() {
+        let mut history = HistoryTree::new(2);
+
+        history.insert("a".chars(), 1);
+        history.insert("ab".chars(), 2);
+        history.insert("a".chars(), 3);
+        assert_eq!(history.remove_by_shortest_prefix("abc".chars()), Some(3));
+
+        assert!(history.undo());
+        assert_eq!(history.tree().get_exact_match("a".chars()), Some(&3));
+        assert!(history.undo());
+        assert_eq!(history.tree().get_exact_match("a".chars()), Some(&1));
+        assert!(!history.undo());
+        assert_eq!(history.tree().get_exact_match("ab".chars()), Some(&2));
+
+        assert!(history.redo());
+        assert_eq!(history.tree().get_exact_match("a".chars()), Some(&3));
+
+        history.remove_exact_match("ab".chars());
+        assert!(!history.can_redo());
+        assert!(history.undo());
+        assert_eq!(history.tree().get_exact_match("ab".chars()), Some(&2));
+    }
+}
@@ -0,0 +1,161 @@
+use std::hash::Hash;
+
+use crate::{FrozenPrefixTree, PrefixMapRead, PrefixTree};
+
+/// A prefix tree where selected subtrees are frozen (see `FrozenPrefixTree`) while the rest of the
+/// tree stays mutable. The lookups go through both parts transparently; the keys at and under a
+/// frozen prefix can't be changed until the prefix is thawed. Frozen prefixes never nest
+#[derive(Debug)]
+pub struct HybridPrefixTree<K: Hash + Eq, V> {
+    /// The entries outside of the frozen subtrees
+    tree: PrefixTree<K, V>,
+    /// The frozen subtrees by their prefixes
+    frozen: PrefixTree<K, FrozenPrefixTree<K, V>>,
+}
+
+impl<K: Hash + Eq, V> Default for HybridPrefixTree<K, V> {
+    fn default() -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            frozen: PrefixTree::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone, V> HybridPrefixTree<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same as `PrefixTree::insert`, but gives the value back if the key is in a frozen subtree
+    pub fn insert(&mut self, key: &[K], value: V) -> Result<Option<V>, V> {
+        if self.is_frozen(key) {
+            return Err(value);
+        }
+        Ok(self.tree.insert(key, value))
+    }
+
+    pub fn get_exact_match(&self, key: &[K]) -> Option<&V> {
+        match self.frozen.get_by_shortest_prefix_with_len(key) {
+            Some((len, frozen)) => frozen.get_exact_match(&key[len..]),
+            None => self.tree.get_exact_match(key),
+        }
+    }
+
+    pub fn get_by_shortest_prefix(&self, key: &[K]) -> Option<&V> {
+        // The mutable part has nothing at or under the frozen prefixes, so all of its matches are
+        // shorter than a frozen prefix on the way
+        self.tree.get_by_shortest_prefix(key).or_else(|| {
+            let (len, frozen) = self.frozen.get_by_shortest_prefix_with_len(key)?;
+            frozen.get_by_shortest_prefix(&key[len..])
+        })
+    }
+
+    pub fn get_by_longest_prefix(&self, key: &[K]) -> Option<&V> {
+        self.frozen
+            .get_by_shortest_prefix_with_len(key)
+            .and_then(|(len, frozen)| frozen.get_by_longest_prefix(&key[len..]))
+            .or_else(|| self.tree.get_by_longest_prefix(key))
+    }
+
+    /// Same as `PrefixTree::remove_exact_match`; the keys in the frozen subtrees are never removed
+    pub fn remove_exact_match(&mut self, key: &[K]) -> Option<V> {
+        self.tree.remove_exact_match(key)
+    }
+
+    /// Returns `true` if the key is at or under a frozen prefix
+    pub fn is_frozen(&self, key: &[K]) -> bool {
+        self.frozen.contains_prefix(key)
+    }
+
+    /// Freezes everything at and under the prefix, thawing the frozen subtrees under it first.
+    /// Returns `false` and changes nothing if the prefix is already inside a frozen subtree
+    pub fn freeze(&mut self, prefix: &[K]) -> bool {
+        if self.is_frozen(prefix) {
+            return false;
+        }
+        let nested = self.frozen.replace_prefix(prefix, PrefixTree::new());
+        let mut subtree = self.tree.replace_prefix(prefix, PrefixTree::new());
+        for (suffix, frozen) in nested.into_chunks(1).flatten() {
+            subtree.replace_prefix(suffix, frozen.thaw());
+        }
+        let frozen = subtree.freeze();
+        if !frozen.is_empty() {
+            self.frozen.insert(prefix, frozen);
+        }
+        true
+    }
+
+    /// Makes the subtree frozen at exactly the prefix mutable again; returns `false` if there was
+    /// none
+    pub fn thaw(&mut self, prefix: &[K]) -> bool {
+        match self.frozen.remove_exact_match(prefix) {
+            Some(frozen) => {
+                self.tree.replace_prefix(prefix, frozen.thaw());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of stored values, frozen or not
+    pub fn len(&self) -> usize {
+        let frozen: usize = self
+            .frozen
+            .iter_sorted()
+            .map(|(_, frozen)| frozen.len())
+            .sum();
+        self.tree.len() + frozen
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone, V> PrefixMapRead<K, V> for HybridPrefixTree<K, V> {
+    fn get_exact_match(&self, key: &[K]) -> Option<&V> {
+        HybridPrefixTree::get_exact_match(self, key)
+    }
+
+    fn get_by_shortest_prefix(&self, key: &[K]) -> Option<&V> {
+        HybridPrefixTree::get_by_shortest_prefix(self, key)
+    }
+
+    fn len(&self) -> usize {
+        HybridPrefixTree::len(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> Vec<char> {
+        key.chars().collect()
+    }
+
+    #[test]
+    fn test_hybrid_tree() {
+        let mut tree = HybridPrefixTree::new();
+        for (index, route) in ["/", "/api/a", "/api/b", "/static/x"].iter().enumerate() {
+            tree.insert(&key(route), index).unwrap();
+        }
+
+        assert!(tree.freeze(&key("/api/")));
+        assert_eq!(tree.insert(&key("/api/c"), 9), Err(9));
+        assert_eq!(tree.insert(&key("/dyn"), 4), Ok(None));
+        assert_eq!(tree.get_exact_match(&key("/api/b")), Some(&2));
+        assert_eq!(tree.get_by_shortest_prefix(&key("/api/a")), Some(&0));
+        assert_eq!(tree.get_by_longest_prefix(&key("/api/ax")), Some(&1));
+        assert_eq!(tree.get_by_longest_prefix(&key("/api/x")), Some(&0));
+        assert!(!tree.freeze(&key("/api/a")));
+
+        assert!(tree.freeze(&key("/")));
+        assert_eq!(tree.len(), 5);
+        assert!(tree.thaw(&key("/")));
+        assert_eq!(tree.insert(&key("/api/c"), 9), Ok(None));
+        assert_eq!(tree.remove_exact_match(&key("/api/a")), Some(1));
+        assert_eq!(tree.len(), 5);
+    }
+}
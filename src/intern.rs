@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::PrefixTree;
+
+/// A prefix tree with string segments where every distinct segment is stored once and shared by
+/// all the nodes it labels, so routes like `api/v1/users` and `api/v2/users` don't allocate
+/// `"api"` and `"users"` twice
+#[derive(Debug, Default)]
+pub struct InternedPrefixTree<V> {
+    tree: PrefixTree<Arc<str>, V>,
+    segments: HashSet<Arc<str>>,
+}
+
+impl<V> InternedPrefixTree<V> {
+    pub fn new() -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            segments: HashSet::new(),
+        }
+    }
+
+    /// Same as `PrefixTree::insert`
+    pub fn insert<'a>(
+        &mut self,
+        sequence: impl IntoIterator<Item = &'a str>,
+        value: V,
+    ) -> Option<V> {
+        let segments = &mut self.segments;
+        self.tree.insert(
+            sequence
+                .into_iter()
+                .map(|segment| intern(segments, segment)),
+            value,
+        )
+    }
+
+    /// Same as `PrefixTree::get_exact_match`
+    pub fn get_exact_match<'a>(&self, sequence: impl IntoIterator<Item = &'a str>) -> Option<&V> {
+        let mut root = &self.tree;
+        for segment in sequence {
+            root = root.subtrees.get(segment)?;
+        }
+        root.value.as_ref()
+    }
+
+    /// Same as `PrefixTree::get_by_shortest_prefix`
+    pub fn get_by_shortest_prefix<'a>(
+        &self,
+        sequence: impl IntoIterator<Item = &'a str>,
+    ) -> Option<&V> {
+        let mut sequence = sequence.into_iter();
+        let mut root = &self.tree;
+        loop {
+            if root.value.is_some() {
+                return root.value.as_ref();
+            }
+            root = root.subtrees.get(sequence.next()?)?;
+        }
+    }
+
+    /// Same as `PrefixTree::remove_exact_match`; the segments stay in the pool until
+    /// `shrink_pool` is called
+    pub fn remove_exact_match<'a>(
+        &mut self,
+        sequence: impl IntoIterator<Item = &'a str>,
+    ) -> Option<V> {
+        let key: Vec<Arc<str>> = sequence
+            .into_iter()
+            .map(|segment| self.segments.get(segment).cloned())
+            .collect::<Option<_>>()?;
+        self.tree.remove_exact_match(&key)
+    }
+
+    /// Drops the pooled segments that no node uses anymore
+    pub fn shrink_pool(&mut self) {
+        self.segments
+            .retain(|segment| Arc::strong_count(segment) > 1);
+        self.segments.shrink_to_fit();
+    }
+
+    /// Returns the number of distinct segments in the pool
+    pub fn pool_len(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn tree(&self) -> &PrefixTree<Arc<str>, V> {
+        &self.tree
+    }
+}
+
+fn intern(segments: &mut HashSet<Arc<str>>, segment: &str) -> Arc<str> {
+    match segments.get(segment) {
+        Some(segment) => segment.clone(),
+        None => {
+            let segment: Arc<str> = segment.into();
+            segments.insert(segment.clone());
+            segment
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interned_prefix_tree() {
+        let mut tree = InternedPrefixTree::new();
+        tree.insert(["api", "v1", "users"], 1);
+        tree.insert(["api", "v2", "users"], 2);
+        assert_eq!(tree.pool_len(), 4);
+
+        let users = |version| &tree.tree().subtrees["api"].subtrees[version].subtrees;
+        let (first, _) = users("v1").get_key_value("users").unwrap();
+        let (second, _) = users("v2").get_key_value("users").unwrap();
+        assert!(Arc::ptr_eq(first, second));
+
+        assert_eq!(tree.get_exact_match(["api", "v2", "users"]), Some(&2));
+        assert_eq!(
+            tree.get_by_shortest_prefix(["api", "v1", "users", "x"]),
+            Some(&1)
+        );
+        assert_eq!(tree.remove_exact_match(["api", "v1", "users"]), Some(1));
+        assert_eq!(tree.remove_exact_match(["unknown"]), None);
+        tree.shrink_pool();
+        assert_eq!(tree.pool_len(), 3);
+    }
+}
@@ -0,0 +1,658 @@
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::vec;
+
+use crate::PrefixTree;
+
+impl<K: Hash + Eq + Ord, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns the immediate subtrees ordered by their keys
+    pub(crate) fn sorted_subtrees(&self) -> Vec<(&K, &Self)> {
+        let mut subtrees: Vec<_> = self.subtrees.iter().collect();
+        subtrees.sort_unstable_by_key(|(key, _)| *key);
+        subtrees
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns an iterator over all the entries of the tree in lexicographic order of their keys.
+    /// Unlike walking `subtrees` directly, the order doesn't depend on the hashing of the keys, so
+    /// it is the same across runs and platforms
+    pub fn iter_sorted(&self) -> SortedIter<'_, K, V, S> {
+        SortedIter {
+            root: Some(self),
+            stack: Vec::new(),
+            path: Vec::new(),
+        }
+    }
+
+    /// Returns an iterator over the entries whose keys start with the prefix (including the
+    /// prefix itself), with their full keys, in lexicographic order of the keys
+    pub fn completions<I: Borrow<K>>(
+        &self,
+        prefix: impl IntoIterator<Item = I>,
+    ) -> SortedIter<'_, K, V, S> {
+        let mut path = Vec::new();
+        let root = self.subtree(
+            prefix
+                .into_iter()
+                .inspect(|item| path.push(item.borrow().clone())),
+        );
+        SortedIter {
+            root,
+            stack: Vec::new(),
+            path,
+        }
+    }
+}
+
+impl<K: Hash + Eq + Ord, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Same as `iter_sorted`, but every key is given as the number of leading items it shares
+    /// with the previous key and the items after them, borrowed from the tree. No key is built in
+    /// full, which suits writers of sorted dumps that store the keys front-coded
+    pub fn iter_delta(&self) -> DeltaIter<'_, K, V, S> {
+        DeltaIter {
+            root: Some(self),
+            stack: Vec::new(),
+            path: Vec::new(),
+            shared: 0,
+        }
+    }
+}
+
+impl<K: Hash + Eq + Ord, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Calls `f` with every entry of the tree in lexicographic order of the keys, like
+    /// `iter_sorted`, but the key is given as a path of references into the tree which is reused
+    /// between the calls, so no key is allocated or cloned
+    pub fn for_each_entry(&self, mut f: impl FnMut(&[&K], &V)) {
+        for_each_node(self, &mut Vec::new(), &mut f);
+    }
+
+    /// Same as `completions` with the keys given as in `for_each_entry`
+    pub fn for_each_completion<I: Borrow<K>>(
+        &self,
+        prefix: impl IntoIterator<Item = I>,
+        mut f: impl FnMut(&[&K], &V),
+    ) {
+        let mut path = Vec::new();
+        let mut root = self;
+        for item in prefix {
+            let (key, subtree) = match root.subtrees.get_key_value(item.borrow()) {
+                Some(child) => child,
+                None => return,
+            };
+            path.push(key);
+            root = subtree;
+        }
+        for_each_node(root, &mut path, &mut f);
+    }
+}
+
+fn for_each_node<'a, K: Hash + Eq + Ord, V, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    path: &mut Vec<&'a K>,
+    f: &mut impl FnMut(&[&K], &V),
+) {
+    if let Some(value) = &root.value {
+        f(path, value);
+    }
+    for (key, subtree) in root.sorted_subtrees() {
+        path.push(key);
+        for_each_node(subtree, path, f);
+        path.pop();
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns up to `limit` entries whose keys start with `prefix`, in lexicographic order of the
+    /// keys, skipping the first `offset` of them. No keys are built for the entries before the
+    /// page, but every skipped subtree is still walked to count its entries, so the time grows
+    /// with the offset; `CountedPrefixTree::complete_page` skips by the stored counts instead
+    pub fn complete_page<I: Borrow<K>>(
+        &self,
+        prefix: impl IntoIterator<Item = I>,
+        offset: usize,
+        limit: usize,
+    ) -> Vec<(Vec<K>, &V)> {
+        let mut path = Vec::new();
+        let mut root = self;
+        for item in prefix {
+            root = match root.subtrees.get(item.borrow()) {
+                Some(subtree) => subtree,
+                None => return Vec::new(),
+            };
+            path.push(item.borrow().clone());
+        }
+        let mut page = Vec::new();
+        collect_page(root, &mut path, &mut { offset }, limit, &mut page);
+        page
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Same as `completions`, but only looks up to `max_extra` items past the prefix, in
+    /// lexicographic order of the keys; the subtrees deeper than that are never visited
+    pub fn completions_within_depth<I: Borrow<K>>(
+        &self,
+        prefix: impl IntoIterator<Item = I>,
+        max_extra: usize,
+    ) -> Vec<(Vec<K>, &V)> {
+        let mut path = Vec::new();
+        let root = self.subtree(
+            prefix
+                .into_iter()
+                .inspect(|item| path.push(item.borrow().clone())),
+        );
+        let mut completions = Vec::new();
+        if let Some(root) = root {
+            collect_within_depth(root, &mut path, max_extra, &mut completions);
+        }
+        completions
+    }
+}
+
+fn collect_within_depth<'a, K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    path: &mut Vec<K>,
+    depth_left: usize,
+    completions: &mut Vec<(Vec<K>, &'a V)>,
+) {
+    if let Some(value) = &root.value {
+        completions.push((path.clone(), value));
+    }
+    if depth_left == 0 {
+        return;
+    }
+    for (key, subtree) in root.sorted_subtrees() {
+        path.push(key.clone());
+        collect_within_depth(subtree, path, depth_left - 1, completions);
+        path.pop();
+    }
+}
+
+fn collect_page<'a, K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    path: &mut Vec<K>,
+    offset: &mut usize,
+    limit: usize,
+    page: &mut Vec<(Vec<K>, &'a V)>,
+) {
+    if let Some(value) = &root.value {
+        if *offset == 0 {
+            if page.len() < limit {
+                page.push((path.clone(), value));
+            }
+        } else {
+            *offset -= 1;
+        }
+    }
+    for (key, subtree) in root.sorted_subtrees() {
+        if page.len() == limit {
+            return;
+        }
+        if *offset > 0 {
+            let len = subtree.len();
+            if len <= *offset {
+                *offset -= len;
+                continue;
+            }
+        }
+        path.push(key.clone());
+        collect_page(subtree, path, offset, limit, page);
+        path.pop();
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns an iterator over the values associated with all the prefixes of the given
+    /// sequence, from the shortest prefix to the longest one, along with the lengths of the
+    /// prefixes. The sequence is consumed lazily, up to the point where it leaves the tree
+    pub fn prefix_matches<I: Borrow<K>, T: IntoIterator<Item = I>>(
+        &self,
+        sequence: T,
+    ) -> PrefixMatches<'_, K, V, T::IntoIter, S> {
+        PrefixMatches {
+            node: Some(self),
+            sequence: sequence.into_iter(),
+            depth: 0,
+            descend: false,
+        }
+    }
+
+    /// Same as `prefix_matches`, but the longest prefix comes first, e.g. for trying the most
+    /// specific handler before falling back to its parents
+    pub fn matches_deepest_first<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Vec<(usize, &V)> {
+        let mut matches: Vec<_> = self.prefix_matches(sequence).collect();
+        matches.reverse();
+        matches
+    }
+}
+
+/// An iterator over the values at the prefixes of a sequence, created by
+/// `PrefixTree::prefix_matches`
+pub struct PrefixMatches<'a, K: Hash + Eq, V, T, S = RandomState> {
+    node: Option<&'a PrefixTree<K, V, S>>,
+    sequence: T,
+    depth: usize,
+    /// Whether `node` was already looked at, so the next item has to be taken before going on;
+    /// the item is only taken when the next match is asked for
+    descend: bool,
+}
+
+impl<'a, K: Hash + Eq, V, I: Borrow<K>, T: Iterator<Item = I>, S: BuildHasher + Clone> Iterator
+    for PrefixMatches<'a, K, V, T, S>
+{
+    type Item = (usize, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.node?;
+            if self.descend {
+                self.node = self
+                    .sequence
+                    .next()
+                    .and_then(|item| node.subtrees.get(item.borrow()));
+                self.depth += 1;
+                self.descend = false;
+                continue;
+            }
+            self.descend = true;
+            if let Some(value) = &node.value {
+                return Some((self.depth, value));
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Consumes the tree and returns an iterator over its entries split into owned chunks of
+    /// `chunk_size` entries each (the last chunk may be smaller), e.g. to hand them out to threads.
+    /// The entries of a chunk come from neighbouring branches. A `chunk_size` of zero is treated as
+    /// one
+    pub fn into_chunks(self, chunk_size: usize) -> IntoChunks<K, V, S> {
+        IntoChunks {
+            stack: vec![(Vec::new(), self)],
+            chunk_size: chunk_size.max(1),
+        }
+    }
+}
+
+/// An iterator over the owned entries of a tree in chunks, created by `PrefixTree::into_chunks`
+pub struct IntoChunks<K: Hash + Eq, V, S = RandomState> {
+    /// The nodes left to drain, with their keys
+    stack: Vec<(Vec<K>, PrefixTree<K, V, S>)>,
+    chunk_size: usize,
+}
+
+impl<K: Hash + Eq + Clone, V, S> Iterator for IntoChunks<K, V, S> {
+    type Item = Vec<(Vec<K>, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = Vec::new();
+        while chunk.len() < self.chunk_size {
+            let (
+                path,
+                PrefixTree {
+                    value, subtrees, ..
+                },
+            ) = match self.stack.pop() {
+                Some(node) => node,
+                None => break,
+            };
+            for (key, subtree) in subtrees {
+                let mut subtree_path = path.clone();
+                subtree_path.push(key);
+                self.stack.push((subtree_path, subtree));
+            }
+            if let Some(value) = value {
+                chunk.push((path, value));
+            }
+        }
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(chunk)
+        }
+    }
+}
+
+/// An iterator over the entries of a tree in lexicographic order of the keys, yielding the full
+/// key of each entry along with its value. Like the other iterators of the crate, it borrows the
+/// tree, so the tree can't be changed while it is iterated; `PrefixTree::snapshot_iter` gives an
+/// iterator over the keys that doesn't borrow it
+pub struct SortedIter<'a, K: Hash + Eq, V, S = RandomState> {
+    root: Option<&'a PrefixTree<K, V, S>>,
+    #[allow(clippy::type_complexity)]
+    stack: Vec<vec::IntoIter<(&'a K, &'a PrefixTree<K, V, S>)>>,
+    path: Vec<K>,
+}
+
+impl<'a, K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> Iterator
+    for SortedIter<'a, K, V, S>
+{
+    type Item = (Vec<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            self.stack.push(root.sorted_subtrees().into_iter());
+            if let Some(value) = &root.value {
+                return Some((self.path.clone(), value));
+            }
+        }
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some((key, subtree)) => {
+                    self.path.push(key.clone());
+                    self.stack.push(subtree.sorted_subtrees().into_iter());
+                    if let Some(value) = &subtree.value {
+                        return Some((self.path.clone(), value));
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                    self.path.pop();
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the entries of a tree in lexicographic order of the keys, created by
+/// `PrefixTree::iter_delta`, yielding every key relative to the previous one
+pub struct DeltaIter<'a, K: Hash + Eq, V, S = RandomState> {
+    root: Option<&'a PrefixTree<K, V, S>>,
+    #[allow(clippy::type_complexity)]
+    stack: Vec<vec::IntoIter<(&'a K, &'a PrefixTree<K, V, S>)>>,
+    path: Vec<&'a K>,
+    /// The length of the common prefix of the path and the last yielded key
+    shared: usize,
+}
+
+impl<'a, K: Hash + Eq, V, S> DeltaIter<'a, K, V, S> {
+    fn delta(&mut self, value: &'a V) -> (usize, Vec<&'a K>, &'a V) {
+        let shared = self.shared;
+        self.shared = self.path.len();
+        (shared, self.path[shared..].to_vec(), value)
+    }
+}
+
+impl<'a, K: Hash + Eq + Ord, V, S: BuildHasher + Clone> Iterator for DeltaIter<'a, K, V, S> {
+    type Item = (usize, Vec<&'a K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            self.stack.push(root.sorted_subtrees().into_iter());
+            if let Some(value) = &root.value {
+                return Some(self.delta(value));
+            }
+        }
+        loop {
+            match self.stack.last_mut()?.next() {
+                Some((key, subtree)) => {
+                    self.path.push(key);
+                    self.stack.push(subtree.sorted_subtrees().into_iter());
+                    if let Some(value) = &subtree.value {
+                        return Some(self.delta(value));
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                    self.path.pop();
+                    self.shared = self.shared.min(self.path.len());
+                }
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Copies the key structure of the whole tree up front, cloning every key item once (in time
+    /// and memory proportional to the number of nodes), and returns an iterator over the current
+    /// keys in lexicographic order that owns the copy, so the tree can be changed during the
+    /// iteration. No values are cloned: they are looked up in the tree when needed, and may be
+    /// gone by then. The full keys are only built as they are yielded
+    pub fn snapshot_iter(&self) -> SnapshotIter<K> {
+        let mut nodes = vec![(0, None, self.value.is_some())];
+        flatten_sorted(self, 1, &mut nodes);
+        nodes.reverse();
+        SnapshotIter {
+            nodes,
+            path: Vec::new(),
+        }
+    }
+}
+
+/// Lists the nodes under the root with their depths in lexicographic order
+fn flatten_sorted<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone>(
+    root: &PrefixTree<K, V, S>,
+    depth: usize,
+    nodes: &mut Vec<(usize, Option<K>, bool)>,
+) {
+    for (key, subtree) in root.sorted_subtrees() {
+        nodes.push((depth, Some(key.clone()), subtree.value.is_some()));
+        flatten_sorted(subtree, depth + 1, nodes);
+    }
+}
+
+/// An owned iterator over a copy of the keys of a tree, created by `PrefixTree::snapshot_iter`
+pub struct SnapshotIter<K> {
+    /// The nodes left to visit with their depths and whether they had values, in reverse
+    /// lexicographic order; only the root has no key item
+    nodes: Vec<(usize, Option<K>, bool)>,
+    path: Vec<K>,
+}
+
+impl<K: Clone> Iterator for SnapshotIter<K> {
+    type Item = Vec<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (depth, item, has_value) = self.nodes.pop()?;
+            self.path.truncate(depth.saturating_sub(1));
+            self.path.extend(item);
+            if has_value {
+                return Some(self.path.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iter_sorted() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["b", "", "ab", "a", "ba", "c"].into_iter().enumerate() {
+            tree.insert(key.chars(), index);
+        }
+
+        let keys: Vec<String> = tree
+            .iter_sorted()
+            .map(|(key, _)| key.into_iter().collect())
+            .collect();
+        assert_eq!(keys, ["", "a", "ab", "b", "ba", "c"]);
+    }
+
+    #[test]
+    fn test_iter_delta() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["car", "cart", "care", "cat", "dog", "do"]
+            .into_iter()
+            .enumerate()
+        {
+            tree.insert(key.chars(), index);
+        }
+
+        let deltas: Vec<(usize, String)> = tree
+            .iter_delta()
+            .map(|(shared, suffix, _)| (shared, suffix.into_iter().collect()))
+            .collect();
+        let expected = [
+            (0, "car"),
+            (3, "e"),
+            (3, "t"),
+            (2, "t"),
+            (0, "do"),
+            (2, "g"),
+        ];
+        let expected: Vec<(usize, String)> = expected
+            .iter()
+            .map(|&(shared, suffix)| (shared, suffix.to_owned()))
+            .collect();
+        assert_eq!(deltas, expected);
+    }
+
+    #[test]
+    fn test_for_each_entry() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["b", "", "ab", "a", "ba"].into_iter().enumerate() {
+            tree.insert(key.chars(), index);
+        }
+
+        let mut entries = Vec::new();
+        tree.for_each_entry(|path, &value| entries.push((path.iter().copied().collect(), value)));
+        let expected: Vec<(String, usize)> = tree
+            .iter_sorted()
+            .map(|(key, &value)| (key.into_iter().collect(), value))
+            .collect();
+        assert_eq!(entries, expected);
+
+        let mut completions = Vec::new();
+        tree.for_each_completion("b".chars(), |path, _| {
+            completions.push(path.iter().copied().collect::<String>())
+        });
+        assert_eq!(completions, ["b", "ba"]);
+        tree.for_each_completion("x".chars(), |_, _| panic!("no completions expected"));
+    }
+
+    #[test]
+    fn test_completions() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["car", "cart", "care", "cat", "dog"]
+            .into_iter()
+            .enumerate()
+        {
+            tree.insert(key.chars(), index);
+        }
+
+        let completions: Vec<(String, usize)> = tree
+            .completions("car".chars())
+            .map(|(key, &value)| (key.into_iter().collect(), value))
+            .collect();
+        assert_eq!(
+            completions,
+            [
+                ("car".to_owned(), 0),
+                ("care".to_owned(), 2),
+                ("cart".to_owned(), 1)
+            ]
+        );
+        assert_eq!(tree.completions("x".chars()).count(), 0);
+        assert_eq!(tree.completions("".chars()).count(), 5);
+    }
+
+    #[test]
+    fn test_snapshot_iter() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["b", "", "ab", "a", "ba"].into_iter().enumerate() {
+            tree.insert(key.chars(), index);
+        }
+
+        let expected: Vec<_> = tree
+            .iter_sorted()
+            .map(|(key, &value)| (key, value))
+            .collect();
+        let mut entries = Vec::new();
+        for key in tree.snapshot_iter() {
+            let value = tree.remove_exact_match(&key).unwrap();
+            entries.push((key, value));
+        }
+        assert_eq!(entries, expected);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn test_prefix_matches() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["", "a", "abc", "abd", "b"].into_iter().enumerate() {
+            tree.insert(key.chars(), index);
+        }
+
+        let matches: Vec<_> = tree.prefix_matches("abcd".chars()).collect();
+        assert_eq!(matches, [(0, &0), (1, &1), (3, &2)]);
+        assert_eq!(tree.prefix_matches("x".chars()).count(), 1);
+        assert_eq!(
+            tree.matches_deepest_first("abd".chars()),
+            [(3, &3), (1, &1), (0, &0)]
+        );
+
+        // A match is given as soon as its last item is read
+        let sequence = "a"
+            .chars()
+            .chain(std::iter::once_with(|| panic!("read too far")));
+        let mut matches = tree.prefix_matches(sequence);
+        assert_eq!(matches.nth(1), Some((1, &1)));
+    }
+
+    #[test]
+    fn test_into_chunks() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["a", "ab", "abc", "b", "bc"].into_iter().enumerate() {
+            tree.insert(key.chars(), index);
+        }
+
+        let chunks: Vec<_> = tree.into_chunks(2).collect();
+        assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), [2, 2, 1]);
+        let mut values: Vec<_> = chunks.into_iter().flatten().collect();
+        values.sort_unstable();
+        assert_eq!(values[1], (vec!['a', 'b'], 1));
+        assert_eq!(values.len(), 5);
+    }
+
+    #[test]
+    fn test_completions_within_depth() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["car", "cart", "carts", "cat", "c", "dog"]
+            .into_iter()
+            .enumerate()
+        {
+            tree.insert(key.chars(), index);
+        }
+
+        let keys = |prefix: &str, max_extra| -> Vec<String> {
+            tree.completions_within_depth(prefix.chars(), max_extra)
+                .into_iter()
+                .map(|(key, _)| key.into_iter().collect())
+                .collect()
+        };
+        assert_eq!(keys("c", 2), ["c", "car", "cat"]);
+        assert_eq!(keys("car", 1), ["car", "cart"]);
+        assert_eq!(keys("ca", 0), [] as [&str; 0]);
+        assert_eq!(keys("x", 5), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn test_complete_page() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["xa", "xab", "xb", "xba", "xbb", "y"]
+            .into_iter()
+            .enumerate()
+        {
+            tree.insert(key.chars(), index);
+        }
+
+        let page: Vec<String> = tree
+            .complete_page("x".chars(), 2, 2)
+            .into_iter()
+            .map(|(key, _)| key.into_iter().collect())
+            .collect();
+        assert_eq!(page, ["xb", "xba"]);
+        assert_eq!(tree.complete_page("x".chars(), 5, 2), []);
+        assert_eq!(tree.complete_page("z".chars(), 0, 2), []);
+    }
+}
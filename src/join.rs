@@ -0,0 +1,136 @@
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Pairs every entry of this tree with every entry of `other` whose key has the same first
+    /// `depth` items, in lexicographic order of the keys; entries with shorter keys are left out.
+    /// Both trees are walked together down to `depth`, so only the prefixes present in both of
+    /// them are visited. Every pair gets its own copies of both keys; `for_each_joined` borrows
+    /// them instead
+    #[allow(clippy::type_complexity)]
+    pub fn join_on_prefix<'a, W, H: BuildHasher + Clone>(
+        &'a self,
+        other: &'a PrefixTree<K, W, H>,
+        depth: usize,
+    ) -> Vec<((Vec<K>, &'a V), (Vec<K>, &'a W))> {
+        let mut pairs = Vec::new();
+        self.for_each_joined(other, depth, |prefix, (left, value), (right, other)| {
+            pairs.push((
+                (joined(prefix, left), value),
+                (joined(prefix, right), other),
+            ))
+        });
+        pairs
+    }
+
+    /// Same as `join_on_prefix`, but calls `f` with every pair instead of collecting them, giving
+    /// the shared prefix and the rest of both keys as paths into the trees, so no key item is
+    /// cloned and the pairs are never stored. Only the entries of `other` under the current
+    /// prefix are listed at a time
+    #[allow(clippy::type_complexity)]
+    pub fn for_each_joined<'a, W, H: BuildHasher + Clone>(
+        &'a self,
+        other: &'a PrefixTree<K, W, H>,
+        depth: usize,
+        mut f: impl FnMut(&[&'a K], (&[&'a K], &'a V), (&[&'a K], &'a W)),
+    ) {
+        join_nodes(self, other, depth, &mut Vec::new(), &mut f);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn join_nodes<'a, K: Hash + Eq + Ord, V, W, S: BuildHasher + Clone, H: BuildHasher + Clone>(
+    left: &'a PrefixTree<K, V, S>,
+    right: &'a PrefixTree<K, W, H>,
+    depth: usize,
+    path: &mut Vec<&'a K>,
+    f: &mut impl FnMut(&[&'a K], (&[&'a K], &'a V), (&[&'a K], &'a W)),
+) {
+    if path.len() < depth {
+        for (key, left) in left.sorted_subtrees() {
+            if let Some(right) = right.subtrees.get(key) {
+                path.push(key);
+                join_nodes(left, right, depth, path, f);
+                path.pop();
+            }
+        }
+        return;
+    }
+    let right = suffixes(right);
+    for (suffix, value) in suffixes(left) {
+        for (other_suffix, other) in &right {
+            f(path, (&suffix, value), (other_suffix, other));
+        }
+    }
+}
+
+/// Lists the entries of the subtree in lexicographic order with their keys relative to it
+fn suffixes<K: Hash + Eq + Ord, V, S: BuildHasher + Clone>(
+    root: &PrefixTree<K, V, S>,
+) -> Vec<(Vec<&K>, &V)> {
+    let mut key = Vec::new();
+    root.iter_delta()
+        .map(|(shared, suffix, value)| {
+            key.truncate(shared);
+            key.extend(suffix);
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+fn joined<K: Clone>(prefix: &[&K], suffix: &[&K]) -> Vec<K> {
+    prefix
+        .iter()
+        .chain(suffix)
+        .map(|&item| item.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_on_prefix() {
+        let mut users = PrefixTree::new();
+        users.insert("eu/alice".chars(), 1);
+        users.insert("eu/bob".chars(), 2);
+        users.insert("us/carol".chars(), 3);
+        users.insert("e".chars(), 4);
+
+        let mut regions = PrefixTree::new();
+        regions.insert("eu".chars(), "Europe");
+        regions.insert("asia".chars(), "Asia");
+
+        let joined: Vec<(String, &str)> = users
+            .join_on_prefix(&regions, 2)
+            .into_iter()
+            .map(|((user, _), (_, region))| (user.into_iter().collect(), *region))
+            .collect();
+        assert_eq!(
+            joined,
+            [
+                ("eu/alice".to_owned(), "Europe"),
+                ("eu/bob".to_owned(), "Europe")
+            ]
+        );
+        assert_eq!(users.join_on_prefix(&regions, 0).len(), 8);
+
+        let mut pairs = Vec::new();
+        users.for_each_joined(&regions, 1, |prefix, (user, _), (region, _)| {
+            let prefix: String = prefix.iter().copied().collect();
+            let user: String = user.iter().copied().collect();
+            let region: String = region.iter().copied().collect();
+            pairs.push((prefix, user, region))
+        });
+        let pairs: Vec<_> = pairs
+            .iter()
+            .map(|(prefix, user, region)| (prefix.as_str(), user.as_str(), region.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [("e", "", "u"), ("e", "u/alice", "u"), ("e", "u/bob", "u")]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::borrow::Borrow;
+use std::fmt::Display;
+use std::hash::{BuildHasher, Hash};
+use std::slice;
+use std::str::Chars;
+
+use crate::PrefixTree;
+
+/// An item of a sequence that can be stored as a key item of type `K`: either a `K` itself or a
+/// reference to a cloneable `K`. This lets `insert` take `&vec`, `slice.iter()` and the like
+/// directly, without `.cloned()`
+pub trait IntoKeyItem<K> {
+    fn into_key_item(self) -> K;
+}
+
+impl<K> IntoKeyItem<K> for K {
+    fn into_key_item(self) -> K {
+        self
+    }
+}
+
+impl<K: Clone> IntoKeyItem<K> for &K {
+    fn into_key_item(self) -> K {
+        self.clone()
+    }
+}
+
+impl IntoKeyItem<String> for &str {
+    fn into_key_item(self) -> String {
+        self.to_owned()
+    }
+}
+
+/// A fixed-size group of displayable segments (a tuple or an array) that can be used as the key of
+/// a tree with `String` key items, e.g. `("users", 42, "posts")`
+pub trait Segments {
+    fn into_segments(self) -> Vec<String>;
+}
+
+impl<T: Display, const N: usize> Segments for [T; N] {
+    fn into_segments(self) -> Vec<String> {
+        self.iter().map(ToString::to_string).collect()
+    }
+}
+
+macro_rules! impl_segments_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Display),+> Segments for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_segments(self) -> Vec<String> {
+                let ($($name,)+) = self;
+                vec![$($name.to_string()),+]
+            }
+        }
+    };
+}
+
+impl_segments_for_tuple!(A);
+impl_segments_for_tuple!(A, B);
+impl_segments_for_tuple!(A, B, C);
+impl_segments_for_tuple!(A, B, C, D);
+impl_segments_for_tuple!(A, B, C, D, E);
+impl_segments_for_tuple!(A, B, C, D, E, F);
+impl_segments_for_tuple!(A, B, C, D, E, F, G);
+impl_segments_for_tuple!(A, B, C, D, E, F, G, H);
+
+impl<V, S: BuildHasher + Clone> PrefixTree<String, V, S> {
+    /// Same as `insert`, but the key is made of displayable segments
+    pub fn insert_segments(&mut self, segments: impl Segments, value: V) -> Option<V> {
+        self.insert(segments.into_segments(), value)
+    }
+
+    /// Same as `get_exact_match`, but the key is made of displayable segments
+    pub fn get_segments(&self, segments: impl Segments) -> Option<&V> {
+        self.get_exact_match(segments.into_segments())
+    }
+}
+
+/// A sequence that is a whole key, for `PrefixTree::get`. A lookup taking it may consume any
+/// number of its items, so nothing is left of it afterwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExactKey<T>(pub T);
+
+/// A sequence to be matched by one of its prefixes, for `PrefixTree::get_prefix`. A lookup
+/// taking it consumes exactly the items of the matched prefix and gives back the rest, e.g. to
+/// match the next token of the input. (`get_by_longest_prefix` can't promise that: it has to
+/// look at the item after the longest match to know that the match is the longest)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrefixQuery<T>(pub T);
+
+impl<'a> From<&'a str> for ExactKey<Chars<'a>> {
+    fn from(key: &'a str) -> Self {
+        Self(key.chars())
+    }
+}
+
+impl<'a, K> From<&'a [K]> for ExactKey<slice::Iter<'a, K>> {
+    fn from(key: &'a [K]) -> Self {
+        Self(key.iter())
+    }
+}
+
+impl<'a, K> From<&'a Vec<K>> for ExactKey<slice::Iter<'a, K>> {
+    fn from(key: &'a Vec<K>) -> Self {
+        Self(key.iter())
+    }
+}
+
+impl<'a> From<&'a str> for PrefixQuery<Chars<'a>> {
+    fn from(sequence: &'a str) -> Self {
+        Self(sequence.chars())
+    }
+}
+
+impl<'a, K> From<&'a [K]> for PrefixQuery<slice::Iter<'a, K>> {
+    fn from(sequence: &'a [K]) -> Self {
+        Self(sequence.iter())
+    }
+}
+
+impl<'a, K> From<&'a Vec<K>> for PrefixQuery<slice::Iter<'a, K>> {
+    fn from(sequence: &'a Vec<K>) -> Self {
+        Self(sequence.iter())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Same as `get_exact_match`
+    pub fn get<I: Borrow<K>, T: IntoIterator<Item = I>>(&self, key: ExactKey<T>) -> Option<&V> {
+        self.get_exact_match(key.0)
+    }
+
+    /// Same as `match_prefix`: returns the value at the shortest prefix of the sequence that is a
+    /// key, along with the unconsumed rest of the sequence
+    pub fn get_prefix<I: Borrow<K>, T: IntoIterator<Item = I>>(
+        &self,
+        query: PrefixQuery<T>,
+    ) -> Option<(&V, PrefixQuery<T::IntoIter>)> {
+        self.match_prefix(query.0)
+            .map(|(value, rest)| (value, PrefixQuery(rest)))
+    }
+}
+
+/// Builds a `PrefixTree` from `key => value` pairs, where a key is anything `PrefixTree::insert`
+/// accepts (including arrays such as `["usr", "bin"]` for trees with `String` key items)
+#[macro_export]
+macro_rules! prefix_tree {
+    ($($key:expr => $value:expr),* $(,)?) => {{
+        let mut tree = $crate::PrefixTree::new();
+        $(
+            tree.insert($key, $value);
+        )*
+        tree
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments() {
+        let mut tree: PrefixTree<String, u32> = prefix_tree! {
+            ["usr", "bin"] => 1,
+            ["usr", "local", "bin"] => 2,
+        };
+        tree.insert_segments(("users", 42, 'x'), 3);
+
+        assert_eq!(tree.get_segments(["usr", "local", "bin"]), Some(&2));
+        assert_eq!(
+            tree.get_exact_match(["users", "42", "x"].map(String::from)),
+            Some(&3)
+        );
+        assert_eq!(tree.get_segments(("usr",)), None);
+    }
+
+    #[test]
+    fn test_query_wrappers() {
+        let mut tree = PrefixTree::new();
+        tree.insert("ab".chars(), 1);
+        tree.insert("c".chars(), 2);
+
+        assert_eq!(tree.get("ab".into()), Some(&1));
+        assert_eq!(tree.get(ExactKey::from(&['a'][..])), None);
+        assert_eq!(tree.get(ExactKey(vec!['c'])), Some(&2));
+
+        let mut tokens = Vec::new();
+        let mut rest = PrefixQuery::from("abcab");
+        while let Some((&token, remainder)) = tree.get_prefix(rest.clone()) {
+            tokens.push(token);
+            rest = remainder;
+        }
+        assert_eq!(tokens, [1, 2, 1]);
+        assert_eq!(rest.0.as_str(), "");
+    }
+}
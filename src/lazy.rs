@@ -0,0 +1,155 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+use crate::{IntoKeyItem, PrefixTree};
+
+/// Fetches values from external storage (a file, a database and so on) by their ids
+pub trait Loader<V> {
+    type Id;
+    type Error;
+
+    fn load(&mut self, id: &Self::Id) -> Result<V, Self::Error>;
+}
+
+/// A value of a `LazyPrefixTree`: either already in memory or still in external storage
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum LazyValue<V, Id> {
+    Loaded(V),
+    External(Id),
+}
+
+/// A prefix tree whose values may live in external storage: only their ids are kept in memory
+/// until the values are first accessed, after which the loaded values are cached in the tree
+#[derive(Debug)]
+pub struct LazyPrefixTree<K: Hash + Eq, V, L: Loader<V>> {
+    tree: PrefixTree<K, LazyValue<V, L::Id>>,
+    loader: L,
+}
+
+impl<K: Hash + Eq, V, L: Loader<V>> LazyPrefixTree<K, V, L> {
+    pub fn new(loader: L) -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            loader,
+        }
+    }
+
+    /// Inserts a value that is already in memory; returns the previous value if there was one
+    pub fn insert<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        value: V,
+    ) -> Option<LazyValue<V, L::Id>> {
+        self.tree.insert(sequence, LazyValue::Loaded(value))
+    }
+
+    /// Inserts a value that will be fetched by its id on first access; returns the previous value
+    /// if there was one
+    pub fn insert_external<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        id: L::Id,
+    ) -> Option<LazyValue<V, L::Id>> {
+        self.tree.insert(sequence, LazyValue::External(id))
+    }
+
+    /// Same as `PrefixTree::get_exact_match_mut`, but loads the value first if it is still in
+    /// external storage. A failed load leaves the entry unloaded
+    pub fn get_exact_match<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Result<Option<&mut V>, L::Error> {
+        match self.tree.get_exact_match_mut(sequence) {
+            Some(value) => load(&mut self.loader, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Same as `PrefixTree::get_by_shortest_prefix_mut`, but loads the value first if it is still
+    /// in external storage. A failed load leaves the entry unloaded
+    pub fn get_by_shortest_prefix<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Result<Option<&mut V>, L::Error> {
+        match self.tree.get_by_shortest_prefix_mut(sequence) {
+            Some(value) => load(&mut self.loader, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the entry without loading it
+    pub fn remove_exact_match<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<LazyValue<V, L::Id>> {
+        self.tree.remove_exact_match(sequence)
+    }
+
+    /// Returns the underlying tree, in which loaded and unloaded values can be told apart
+    pub fn tree(&self) -> &PrefixTree<K, LazyValue<V, L::Id>> {
+        &self.tree
+    }
+}
+
+fn load<'a, V, L: Loader<V>>(
+    loader: &mut L,
+    value: &'a mut LazyValue<V, L::Id>,
+) -> Result<&'a mut V, L::Error> {
+    if let LazyValue::External(id) = value {
+        *value = LazyValue::Loaded(loader.load(id)?);
+    }
+    match value {
+        LazyValue::Loaded(value) => Ok(value),
+        LazyValue::External(_) => unreachable!("the value was loaded above"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Storage {
+        values: Vec<String>,
+        loads: usize,
+    }
+
+    impl Loader<String> for Storage {
+        type Id = usize;
+        type Error = ();
+
+        fn load(&mut self, id: &usize) -> Result<String, ()> {
+            self.loads += 1;
+            self.values.get(*id).cloned().ok_or(())
+        }
+    }
+
+    #[test]
+    fn test_lazy_prefix_tree() {
+        let mut tree = LazyPrefixTree::new(Storage {
+            values: vec!["zero".to_owned(), "one".to_owned()],
+            loads: 0,
+        });
+        tree.insert_external("a".chars(), 1);
+        tree.insert_external("b".chars(), 5);
+        tree.insert("c".chars(), "inline".to_owned());
+
+        assert_eq!(
+            tree.get_exact_match("a".chars()),
+            Ok(Some(&mut "one".to_owned()))
+        );
+        assert_eq!(
+            tree.get_by_shortest_prefix("ax".chars()),
+            Ok(Some(&mut "one".to_owned()))
+        );
+        assert_eq!(tree.get_exact_match("b".chars()), Err(()));
+        assert_eq!(
+            tree.tree().get_exact_match("b".chars()),
+            Some(&LazyValue::External(5))
+        );
+        assert_eq!(
+            tree.get_exact_match("c".chars()),
+            Ok(Some(&mut "inline".to_owned()))
+        );
+        assert_eq!(tree.loader.loads, 2);
+    }
+}
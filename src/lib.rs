@@ -1,39 +1,257 @@
 use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash};
+use std::iter::Chain;
+use std::vec;
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct PrefixTree<K: Hash + Eq, V> {
+mod adaptive;
+mod any;
+mod codec;
+mod collect;
+mod config;
+mod counted;
+mod cursor;
+mod decompose;
+mod dedup;
+mod dump;
+mod fanout;
+mod flat;
+mod frozen;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+mod glob;
+mod hasher;
+mod history;
+mod hybrid;
+mod intern;
+mod iter;
+mod join;
+mod key;
+mod lazy;
+mod maintenance;
+mod map_like;
+mod merge;
+mod metadata;
+mod metrics;
+mod negative;
+mod normalized;
+mod op;
+mod ordered;
+mod path;
+mod persist;
+mod queries;
+mod quota;
+mod rank;
+mod record;
+mod relabel;
+mod scan;
+mod search;
+mod session;
+mod stats;
+mod table;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod tombstone;
+mod utf8;
+
+use metrics::counters;
+
+pub use adaptive::{AdaptivePrefixTree, LayoutStats};
+pub use any::{AnyPrefixTree, AnyValue};
+pub use codec::{CodecPrefixTree, ValueCodec};
+pub use collect::CollectPrefixTree;
+pub use config::Merge;
+pub use counted::CountedPrefixTree;
+pub use cursor::Cursor;
+pub use dedup::DedupWindow;
+pub use fanout::{FanoutExceeded, FanoutLimitedTree};
+pub use flat::{KeyEscaping, MalformedKey};
+pub use frozen::FrozenPrefixTree;
+pub use glob::GlobPrefixTree;
+pub use hasher::SeededState;
+pub use history::HistoryTree;
+pub use hybrid::HybridPrefixTree;
+pub use intern::InternedPrefixTree;
+pub use iter::{DeltaIter, IntoChunks, PrefixMatches, SnapshotIter, SortedIter};
+pub use key::{ExactKey, IntoKeyItem, PrefixQuery, Segments};
+pub use lazy::{LazyPrefixTree, LazyValue, Loader};
+pub use maintenance::{MaintenanceBudget, MaintenanceCursor};
+pub use map_like::{PrefixMapLike, PrefixMapRead};
+pub use merge::Conflict;
+pub use metadata::{Metadata, MetadataPrefixTree};
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
+pub use negative::NegativeCacheTree;
+pub use normalized::NormalizedPrefixTree;
+pub use op::Op;
+pub use ordered::OrderedPrefixTree;
+pub use path::{PathOptions, PathPrefixTree};
+pub use persist::ReadLimits;
+pub use queries::{compile_queries, CompiledQueries, QueryAnswer};
+pub use quota::{QuotaExceeded, QuotaRule, QuotaTree};
+pub use rank::Ranker;
+pub use record::{OpSink, RecordingTree};
+pub use scan::Scanner;
+pub use search::{Automaton, QueryItem};
+pub use session::{Appender, InsertSession, SubtreeMut};
+pub use stats::TreeStats;
+pub use table::{TableError, TransitionTable};
+pub use tombstone::TombstoneTree;
+pub use utf8::InvalidUtf8;
+
+/// A prefix tree; `S` is the hasher of the children maps, which all the nodes share. The empty
+/// sequence is a key like any other, whose value is stored at the root (see `root_value`)
+#[derive(Debug)]
+pub struct PrefixTree<K: Hash + Eq, V, S = RandomState> {
     pub value: Option<V>,
-    pub subtrees: HashMap<K, PrefixTree<K, V>>,
+    pub subtrees: HashMap<K, PrefixTree<K, V, S>, S>,
+    #[cfg(feature = "metrics")]
+    metrics: metrics::Counters,
 }
 
-impl<K: Hash + Eq, V> Default for PrefixTree<K, V> {
+impl<K: Hash + Eq, V, S: Default> Default for PrefixTree<K, V, S> {
     fn default() -> Self {
         Self {
             value: None,
-            subtrees: HashMap::new(),
+            subtrees: HashMap::default(),
+            #[cfg(feature = "metrics")]
+            metrics: metrics::Counters::default(),
         }
     }
 }
 
+impl<K: Hash + Eq, V: PartialEq, S: BuildHasher> PartialEq for PrefixTree<K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.subtrees == other.subtrees
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for PrefixTree<K, V, S> {}
+
+/// Where `PrefixTree::walk` stopped
+#[derive(Debug)]
+pub struct Walk<'a, K: Hash + Eq, V, I, S = RandomState> {
+    /// The deepest node reached
+    pub node: &'a PrefixTree<K, V, S>,
+    /// The number of items that led to the node
+    pub consumed: usize,
+    pub end: WalkEnd<I>,
+}
+
+/// Why `PrefixTree::walk` stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkEnd<I> {
+    /// All the items were consumed
+    EndOfInput,
+    /// The node has no child for this item (which was consumed too)
+    MissingChild(I),
+}
+
+/// The result of `PrefixTree::lookup`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Match<'a, V> {
+    /// The whole sequence is a key
+    Exact(&'a V),
+    /// The longest prefix of the sequence that is a key is `matched_len` items long
+    Prefix { value: &'a V, matched_len: usize },
+    /// No prefix of the sequence is a key
+    None,
+}
+
 impl<K: Hash + Eq, V> PrefixTree<K, V> {
     pub fn new() -> Self {
         Self::default()
     }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Creates an empty tree whose nodes hash their children with `hash_builder` (e.g. a
+    /// `SeededState`)
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            value: None,
+            subtrees: HashMap::with_hasher(hash_builder),
+            #[cfg(feature = "metrics")]
+            metrics: metrics::Counters::default(),
+        }
+    }
 
     /// Inserts the specified value at the specified key; returns the previous value at the same
     /// key if there was one before
-    pub fn insert(&mut self, sequence: impl IntoIterator<Item = K>, value: V) -> Option<V> {
-        let sequence = sequence.into_iter();
-        let mut root = self;
-        for item in sequence {
-            root = root
-                .subtrees
-                .entry(item)
-                .or_insert_with(|| PrefixTree::new());
+    pub fn insert<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        value: V,
+    ) -> Option<V> {
+        self.slot(sequence).replace(value)
+    }
+
+    /// Returns a mutable reference to the value at the specified key, inserting the result of
+    /// `make_value` first if there was no value; the flag tells whether the value was already
+    /// present
+    pub fn probe<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        make_value: impl FnOnce() -> V,
+    ) -> (bool, &mut V) {
+        let slot = self.slot(sequence);
+        (slot.is_some(), slot.get_or_insert_with(make_value))
+    }
+
+    /// Replaces everything stored at and under the prefix with the contents of `new_subtree` in one
+    /// step and returns the replaced contents as a tree of their own (keyed relative to the
+    /// prefix)
+    pub fn replace_prefix<I: IntoKeyItem<K>>(
+        &mut self,
+        prefix: impl IntoIterator<Item = I>,
+        new_subtree: Self,
+    ) -> Self {
+        let prefix: Vec<K> = prefix.into_iter().map(IntoKeyItem::into_key_item).collect();
+        if new_subtree.is_empty() {
+            // Creating the nodes down to the prefix would leave a dead branch
+            return take_subtree(self, &prefix);
         }
-        root.value.replace(value)
+        let counters = counters!(self.metrics);
+        let (value, subtrees) =
+            descend_creating(&mut self.value, &mut self.subtrees, counters, prefix);
+        let PrefixTree {
+            value: new_value,
+            subtrees: new_subtrees,
+            ..
+        } = new_subtree;
+        let mut old = PrefixTree::with_hasher(subtrees.hasher().clone());
+        old.value = std::mem::replace(value, new_value);
+        old.subtrees = std::mem::replace(subtrees, new_subtrees);
+        old
+    }
+
+    /// Returns the value at the empty key. The empty key is a prefix of every sequence, so while it
+    /// has a value, `get_by_shortest_prefix` returns that value for any sequence, and
+    /// `get_by_longest_prefix` falls back to it
+    pub fn root_value(&self) -> Option<&V> {
+        self.value.as_ref()
+    }
+
+    /// Sets the value at the empty key; returns the previous one if there was one before
+    pub fn set_root_value(&mut self, value: V) -> Option<V> {
+        self.value.replace(value)
+    }
+
+    /// Removes the value at the empty key and returns it. The root node itself always stays, so
+    /// unlike removing other keys this never prunes anything
+    pub fn take_root_value(&mut self) -> Option<V> {
+        self.value.take()
+    }
+
+    /// Returns the value slot of the node at the specified key, creating the missing nodes on the
+    /// way
+    pub(crate) fn slot<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> &mut Option<V> {
+        let counters = counters!(self.metrics);
+        descend_creating(&mut self.value, &mut self.subtrees, counters, sequence).0
     }
 
     /// Returns an immutable reference to the value associated with the shortest prefix of the
@@ -42,20 +260,88 @@ impl<K: Hash + Eq, V> PrefixTree<K, V> {
         &self,
         sequence: impl IntoIterator<Item = I>,
     ) -> Option<&V> {
+        self.get_by_shortest_prefix_with_len(sequence)
+            .map(|(_, value)| value)
+    }
+
+    /// Same as `get_by_shortest_prefix`, but also returns the length of the matched prefix, so the
+    /// caller knows how many items of the sequence the match took
+    pub fn get_by_shortest_prefix_with_len<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(usize, &V)> {
         let mut sequence = sequence.into_iter();
+        let mut probe = counters!(self.metrics).probe();
         let mut root = self;
+        let mut len = 0;
         loop {
-            if matches!(root.value, Some(_)) {
-                return (&root.value).into();
+            probe.visit();
+            if let Some(value) = &root.value {
+                probe.hit(counters!(root.metrics));
+                return Some((len, value));
             }
-            root = match sequence
+            root = sequence
                 .next()
-                .and_then(|item| root.subtrees.get(item.borrow()))
-            {
+                .and_then(|item| root.subtrees.get(item.borrow()))?;
+            len += 1;
+        }
+    }
+
+    /// Same as `get_by_shortest_prefix`, but also returns the rest of the sequence: the items
+    /// after the matched prefix, none of which were consumed by the lookup. This makes it possible
+    /// to parse a sequence of keys by matching them one after another
+    pub fn match_prefix<I: Borrow<K>, T: IntoIterator<Item = I>>(
+        &self,
+        sequence: T,
+    ) -> Option<(&V, T::IntoIter)> {
+        let mut sequence = sequence.into_iter();
+        let mut probe = counters!(self.metrics).probe();
+        let mut root = self;
+        loop {
+            probe.visit();
+            if let Some(value) = &root.value {
+                probe.hit(counters!(root.metrics));
+                return Some((value, sequence));
+            }
+            root = sequence
+                .next()
+                .and_then(|item| root.subtrees.get(item.borrow()))?;
+        }
+    }
+
+    /// Same as `get_by_longest_prefix`, but also returns the rest of the sequence after the matched
+    /// prefix. Finding the longest prefix takes reading past it, so the items read after the match
+    /// are buffered and given back in front of the ones never read
+    #[allow(clippy::type_complexity)]
+    pub fn match_longest_prefix<I: Borrow<K>, T: IntoIterator<Item = I>>(
+        &self,
+        sequence: T,
+    ) -> Option<(&V, Chain<vec::IntoIter<I>, T::IntoIter>)> {
+        let mut sequence = sequence.into_iter();
+        let mut probe = counters!(self.metrics).probe();
+        let mut root = self;
+        let mut found = None;
+        let mut read_past = Vec::new();
+        loop {
+            probe.visit();
+            if root.value.is_some() {
+                found = Some(root);
+                read_past.clear();
+            }
+            let item = match sequence.next() {
+                Some(item) => item,
+                None => break,
+            };
+            let subtree = root.subtrees.get(item.borrow());
+            read_past.push(item);
+            root = match subtree {
                 Some(subtree) => subtree,
-                None => return None,
+                None => break,
             };
         }
+        let found = found?;
+        probe.hit(counters!(found.metrics));
+        Some((found.value.as_ref()?, read_past.into_iter().chain(sequence)))
     }
 
     /// Returns a mutable reference to the value associated with the shortest prefix of the given
@@ -65,18 +351,20 @@ impl<K: Hash + Eq, V> PrefixTree<K, V> {
         sequence: impl IntoIterator<Item = I>,
     ) -> Option<&mut V> {
         let mut sequence = sequence.into_iter();
-        let mut root = self;
+        let mut probe = counters!(self.metrics).probe();
+        let mut entry = counters!(self.metrics);
+        let (mut value, mut subtrees) = (&mut self.value, &mut self.subtrees);
         loop {
-            if matches!(root.value, Some(_)) {
-                return (&mut root.value).into();
+            probe.visit();
+            if value.is_some() {
+                probe.hit(entry);
+                return value.into();
             }
-            root = match sequence
+            let subtree = sequence
                 .next()
-                .and_then(|item| root.subtrees.get_mut(item.borrow()))
-            {
-                Some(subtree) => subtree,
-                None => return None,
-            };
+                .and_then(|item| subtrees.get_mut(item.borrow()))?;
+            entry = counters!(subtree.metrics);
+            (value, subtrees) = (&mut subtree.value, &mut subtree.subtrees);
         }
     }
 
@@ -87,14 +375,20 @@ impl<K: Hash + Eq, V> PrefixTree<K, V> {
         sequence: impl IntoIterator<Item = I>,
     ) -> Option<&mut V> {
         let sequence = sequence.into_iter();
-        let mut root = self;
+        let mut probe = counters!(self.metrics).probe();
+        let mut entry = counters!(self.metrics);
+        let (mut value, mut subtrees) = (&mut self.value, &mut self.subtrees);
+        probe.visit();
         for item in sequence {
-            root = match root.subtrees.get_mut(item.borrow()) {
-                Some(subtree) => subtree,
-                None => return None,
-            };
+            let subtree = subtrees.get_mut(item.borrow())?;
+            probe.visit();
+            entry = counters!(subtree.metrics);
+            (value, subtrees) = (&mut subtree.value, &mut subtree.subtrees);
         }
-        (&mut root.value).into()
+        if value.is_some() {
+            probe.hit(entry);
+        }
+        value.into()
     }
 
     /// Returns an immutable reference to the value associated with the exact match of the
@@ -104,14 +398,262 @@ impl<K: Hash + Eq, V> PrefixTree<K, V> {
         sequence: impl IntoIterator<Item = I>,
     ) -> Option<&V> {
         let sequence = sequence.into_iter();
+        let mut probe = counters!(self.metrics).probe();
+        let mut root = self;
+        probe.visit();
+        for item in sequence {
+            root = root.subtrees.get(item.borrow())?;
+            probe.visit();
+        }
+        if root.value.is_some() {
+            probe.hit(counters!(root.metrics));
+        }
+        (&root.value).into()
+    }
+
+    /// Returns the node at the given prefix, which holds the entries under the prefix keyed
+    /// relative to it (or `None` if no key starts with the prefix)
+    pub fn subtree<I: Borrow<K>>(&self, prefix: impl IntoIterator<Item = I>) -> Option<&Self> {
+        let mut root = self;
+        for item in prefix {
+            root = root.subtrees.get(item.borrow())?;
+        }
+        Some(root)
+    }
+
+    /// Returns the items that can follow the prefix in the keys, each with the value at the prefix
+    /// extended by it, in no particular order (nothing if no key starts with the prefix)
+    pub fn children<I: Borrow<K>>(
+        &self,
+        prefix: impl IntoIterator<Item = I>,
+    ) -> impl Iterator<Item = (&K, Option<&V>)> {
+        self.subtree(prefix)
+            .into_iter()
+            .flat_map(|node| &node.subtrees)
+            .map(|(item, subtree)| (item, subtree.value.as_ref()))
+    }
+
+    /// Goes down the tree along the sequence as far as possible, reporting the node it got to,
+    /// the number of items it took and why it stopped, for callers with their own policies on
+    /// top of the walk (like backtracking or skipping an unknown item)
+    pub fn walk<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Walk<'_, K, V, I, S> {
+        let mut node = self;
+        let mut consumed = 0;
+        for item in sequence {
+            node = match node.subtrees.get(item.borrow()) {
+                Some(subtree) => subtree,
+                None => {
+                    return Walk {
+                        node,
+                        consumed,
+                        end: WalkEnd::MissingChild(item),
+                    }
+                }
+            };
+            consumed += 1;
+        }
+        Walk {
+            node,
+            consumed,
+            end: WalkEnd::EndOfInput,
+        }
+    }
+
+    /// Returns `true` if there is a value at exactly the given sequence
+    pub fn contains_exact<I: Borrow<K>>(&self, sequence: impl IntoIterator<Item = I>) -> bool {
+        self.subtree(sequence)
+            .map_or(false, |subtree| subtree.value.is_some())
+    }
+
+    /// Returns `true` if there is a value at some prefix of the given sequence (including the
+    /// sequence itself); stops at the first such prefix
+    pub fn contains_prefix<I: Borrow<K>>(&self, sequence: impl IntoIterator<Item = I>) -> bool {
+        let mut sequence = sequence.into_iter();
+        let mut root = self;
+        while root.value.is_none() {
+            root = match sequence
+                .next()
+                .and_then(|item| root.subtrees.get(item.borrow()))
+            {
+                Some(subtree) => subtree,
+                None => return false,
+            };
+        }
+        true
+    }
+
+    /// Returns `true` if the given sequence is a prefix of some key that has a value (including
+    /// that key itself)
+    pub fn is_prefix<I: Borrow<K>>(&self, sequence: impl IntoIterator<Item = I>) -> bool {
+        // Branches without values are left by editing the public fields by hand or by an
+        // `Appender` dropped without `finish`
+        self.subtree(sequence)
+            .map_or(false, |subtree| !subtree.is_empty())
+    }
+
+    /// Returns an immutable reference to the value associated with the longest prefix of the
+    /// given sequence (or `None` if no prefixes were found)
+    pub fn get_by_longest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        self.get_by_longest_prefix_with_len(sequence)
+            .map(|(_, value)| value)
+    }
+
+    /// Same as `get_by_longest_prefix`, but also returns the length of the matched prefix, so the
+    /// caller knows how many items of the sequence the match took
+    pub fn get_by_longest_prefix_with_len<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(usize, &V)> {
+        let mut sequence = sequence.into_iter();
+        let mut probe = counters!(self.metrics).probe();
+        let mut root = self;
+        let mut len = 0;
+        let mut found = None;
+        loop {
+            probe.visit();
+            if root.value.is_some() {
+                found = Some((len, root));
+            }
+            root = match sequence
+                .next()
+                .and_then(|item| root.subtrees.get(item.borrow()))
+            {
+                Some(subtree) => subtree,
+                None => break,
+            };
+            len += 1;
+        }
+        let (len, found) = found?;
+        probe.hit(counters!(found.metrics));
+        Some((len, found.value.as_ref()?))
+    }
+
+    /// Tells in one walk whether the sequence is a key or else which of its prefixes is the
+    /// longest key, as `get_exact_match` followed by `get_by_longest_prefix_with_len` would
+    pub fn lookup<I: Borrow<K>>(&self, sequence: impl IntoIterator<Item = I>) -> Match<'_, V> {
+        let mut sequence = sequence.into_iter();
+        let mut probe = counters!(self.metrics).probe();
+        let mut root = self;
+        let mut len = 0;
+        let mut found = None;
+        let item = loop {
+            probe.visit();
+            let item = match sequence.next() {
+                Some(item) => item,
+                None => break None,
+            };
+            if root.value.is_some() {
+                found = Some((len, root));
+            }
+            root = match root.subtrees.get(item.borrow()) {
+                Some(subtree) => subtree,
+                None => break Some(item),
+            };
+            len += 1;
+        };
+        if let (Some(value), None) = (&root.value, item) {
+            probe.hit(counters!(root.metrics));
+            return Match::Exact(value);
+        }
+        match found {
+            Some((matched_len, found)) => {
+                probe.hit(counters!(found.metrics));
+                match &found.value {
+                    Some(value) => Match::Prefix { value, matched_len },
+                    None => Match::None,
+                }
+            }
+            None => Match::None,
+        }
+    }
+
+    /// Returns a mutable reference to the value associated with the longest prefix of the given
+    /// sequence (or `None` if no prefixes were found)
+    pub fn get_by_longest_prefix_mut<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&mut V> {
+        let prefix = self.longest_prefix(sequence)?;
+        self.get_exact_match_mut(prefix.iter().map(Borrow::borrow))
+    }
+
+    /// Returns the longest prefix of the sequence that has a value. The sequence is only read
+    /// while it stays in the tree, and only the items of the path are buffered
+    fn longest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<Vec<I>> {
         let mut root = self;
+        let mut found = root.value.as_ref().map(|_| 0);
+        let mut path = Vec::new();
         for item in sequence {
             root = match root.subtrees.get(item.borrow()) {
                 Some(subtree) => subtree,
-                None => return None,
+                None => break,
+            };
+            path.push(item);
+            if root.value.is_some() {
+                found = Some(path.len());
+            }
+        }
+        path.truncate(found?);
+        Some(path)
+    }
+
+    /// Same as `get_exact_match`, but the whole sequence is always walked, with one lookup per
+    /// item, even after the first missing item, so the time taken depends on the length of the
+    /// sequence rather than on how much of it matched. Meant for keys derived from secrets; note
+    /// that hashing and comparing the items may still take data-dependent time
+    pub fn get_exact_match_timing_safe<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        let mut root = self;
+        let mut matched = true;
+        for item in sequence {
+            let subtree = root.subtrees.get(item.borrow());
+            matched &= subtree.is_some();
+            root = subtree.unwrap_or(root);
+        }
+        root.value.as_ref().filter(|_| matched)
+    }
+
+    /// Experimental: returns an immutable reference to the value with the lowest `cost` among the
+    /// values associated with all prefixes of the given sequence (or `None` if no prefixes were
+    /// found). Ties are won by the longer prefix. The sequence is consumed up to the point where
+    /// it leaves the tree
+    pub fn get_by_lowest_cost_prefix<I: Borrow<K>, C: Ord>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+        mut cost: impl FnMut(&V) -> C,
+    ) -> Option<&V> {
+        let mut sequence = sequence.into_iter();
+        let mut root = self;
+        let mut best: Option<(C, &V)> = None;
+        loop {
+            if let Some(value) = &root.value {
+                let value_cost = cost(value);
+                if best
+                    .as_ref()
+                    .map_or(true, |(best_cost, _)| value_cost <= *best_cost)
+                {
+                    best = Some((value_cost, value));
+                }
+            }
+            root = match sequence
+                .next()
+                .and_then(|item| root.subtrees.get(item.borrow()))
+            {
+                Some(subtree) => subtree,
+                None => return best.map(|(_, value)| value),
             };
         }
-        (&root.value).into()
     }
 
     /// Removes the value associated with the exact match of the given sequence from the tree and
@@ -125,10 +667,7 @@ impl<K: Hash + Eq, V> PrefixTree<K, V> {
         let mut keys = Vec::new();
         for item in sequence {
             let old_root = root as *mut _;
-            root = match root.subtrees.get_mut(item.borrow()) {
-                Some(subtree) => subtree,
-                None => return None,
-            };
+            root = root.subtrees.get_mut(item.borrow())?;
             keys.push((old_root, item));
         }
         let result = root.value.take();
@@ -157,11 +696,20 @@ impl<K: Hash + Eq, V> PrefixTree<K, V> {
         &mut self,
         sequence: impl IntoIterator<Item = I>,
     ) -> Option<V> {
+        self.remove_by_shortest_prefix_with_len(sequence)
+            .map(|(_, value)| value)
+    }
+
+    /// Same as `remove_by_shortest_prefix`, but also returns the length of the matched prefix
+    pub fn remove_by_shortest_prefix_with_len<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(usize, V)> {
         let mut sequence = sequence.into_iter();
         let mut root = self;
         let mut keys = Vec::new();
         loop {
-            if matches!(root.value, Some(_)) {
+            if root.value.is_some() {
                 break;
             }
             let old_root = root as *mut _;
@@ -176,7 +724,7 @@ impl<K: Hash + Eq, V> PrefixTree<K, V> {
             };
             keys.push((old_root, item));
         }
-        let result = root.value.take();
+        let result = root.value.take().map(|value| (keys.len(), value));
         let mut roots = keys.into_iter().rev();
         let mut root: *mut _ = root;
         loop {
@@ -195,6 +743,197 @@ impl<K: Hash + Eq, V> PrefixTree<K, V> {
         }
         result
     }
+
+    /// Removes the value associated with the longest prefix of the given sequence from the tree
+    /// and returns it (or returns `None` if no matching value was found)
+    pub fn remove_by_longest_prefix<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<V> {
+        self.remove_by_longest_prefix_with_len(sequence)
+            .map(|(_, value)| value)
+    }
+
+    /// Same as `remove_by_longest_prefix`, but also returns the length of the matched prefix
+    pub fn remove_by_longest_prefix_with_len<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(usize, V)> {
+        let prefix = self.longest_prefix(sequence)?;
+        let value = self.remove_exact_match(prefix.iter().map(Borrow::borrow))?;
+        Some((prefix.len(), value))
+    }
+
+    /// Returns the counters of the lookups and insertions made through this tree (calls made on
+    /// its subtrees are counted by the subtrees themselves)
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.snapshot()
+    }
+
+    /// Sets all the counters returned by `metrics` to zero
+    #[cfg(feature = "metrics")]
+    pub fn reset_metrics(&self) {
+        self.metrics.reset();
+    }
+
+    /// Brings the tree to its canonical form: removes branches that hold no values (left by
+    /// editing the public fields by hand or by an `Appender` dropped without `finish`) and
+    /// releases the spare capacity of the children maps. Returns `true` if any branches were
+    /// removed
+    pub fn canonicalize(&mut self) -> bool {
+        let mut changed = false;
+        self.subtrees.retain(|_, subtree| {
+            changed |= subtree.canonicalize();
+            let dead = subtree.value.is_none() && subtree.subtrees.is_empty();
+            changed |= dead;
+            !dead
+        });
+        self.subtrees.shrink_to_fit();
+        changed
+    }
+
+    /// Returns the number of values stored in the tree; this walks the whole tree
+    pub fn len(&self) -> usize {
+        self.value.iter().count() + self.subtrees.values().map(Self::len).sum::<usize>()
+    }
+
+    /// Returns `true` if the tree stores no values
+    pub fn is_empty(&self) -> bool {
+        self.value.is_none() && self.subtrees.values().all(Self::is_empty)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Same as `get_exact_match`, but also returns the key
+    pub fn get_key_value<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(Vec<K>, &V)> {
+        let mut key = Vec::new();
+        let value = self.get_exact_match(
+            sequence
+                .into_iter()
+                .inspect(|item| key.push(item.borrow().clone())),
+        )?;
+        Some((key, value))
+    }
+
+    /// Same as `get_by_shortest_prefix`, but also returns the matched key
+    pub fn get_key_value_by_shortest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(Vec<K>, &V)> {
+        let mut key = Vec::new();
+        let (_, value) = self.get_by_shortest_prefix_with_len(
+            sequence
+                .into_iter()
+                .inspect(|item| key.push(item.borrow().clone())),
+        )?;
+        Some((key, value))
+    }
+
+    /// Same as `get_by_longest_prefix`, but also returns the matched key
+    pub fn get_key_value_by_longest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(Vec<K>, &V)> {
+        let mut key = Vec::new();
+        let (len, value) = self.get_by_longest_prefix_with_len(
+            sequence
+                .into_iter()
+                .inspect(|item| key.push(item.borrow().clone())),
+        )?;
+        // The lookup reads the items after the match until it leaves the tree
+        key.truncate(len);
+        Some((key, value))
+    }
+}
+
+impl<K: Hash + Eq + Copy, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Same as `insert`, but takes the key as a slice of `Copy` items (e.g. bytes), which avoids
+    /// the iterator adaptors
+    pub fn insert_copied(&mut self, sequence: &[K], value: V) -> Option<V> {
+        let counters = counters!(self.metrics);
+        let (mut slot, mut subtrees) = (&mut self.value, &mut self.subtrees);
+        for &item in sequence {
+            let hash_builder = subtrees.hasher().clone();
+            let subtree = subtrees.entry(item).or_insert_with(|| {
+                counters.allocation();
+                PrefixTree::with_hasher(hash_builder)
+            });
+            (slot, subtrees) = (&mut subtree.value, &mut subtree.subtrees);
+        }
+        slot.replace(value)
+    }
+
+    /// Same as `get_exact_match`, but takes the key as a slice of `Copy` items (e.g. bytes)
+    pub fn get_exact_match_slice(&self, sequence: &[K]) -> Option<&V> {
+        let mut probe = counters!(self.metrics).probe();
+        let mut root = self;
+        probe.visit();
+        for item in sequence {
+            root = root.subtrees.get(item)?;
+            probe.visit();
+        }
+        if root.value.is_some() {
+            probe.hit(counters!(root.metrics));
+        }
+        root.value.as_ref()
+    }
+}
+
+/// Moves the contents of the node at the prefix out to a new tree, removing the branch leading to
+/// the node if nothing else is stored on it
+fn take_subtree<K: Hash + Eq, V, S: BuildHasher + Clone>(
+    root: &mut PrefixTree<K, V, S>,
+    prefix: &[K],
+) -> PrefixTree<K, V, S> {
+    let hash_builder = root.subtrees.hasher().clone();
+    let (first, rest) = match prefix.split_first() {
+        Some(split) => split,
+        None => {
+            let mut taken = PrefixTree::with_hasher(hash_builder.clone());
+            taken.value = root.value.take();
+            taken.subtrees =
+                std::mem::replace(&mut root.subtrees, HashMap::with_hasher(hash_builder));
+            return taken;
+        }
+    };
+    let subtree = match root.subtrees.get_mut(first) {
+        Some(subtree) => subtree,
+        None => return PrefixTree::with_hasher(hash_builder),
+    };
+    let taken = take_subtree(subtree, rest);
+    if subtree.value.is_none() && subtree.subtrees.is_empty() {
+        root.subtrees.remove(first);
+    }
+    taken
+}
+
+/// Walks down the sequence from the node with the given fields, creating the missing nodes (and
+/// counting them in `counters`), and returns the fields of the node at the end of the sequence.
+/// Taking the fields rather than the node lets the counters stay borrowed from the node where the
+/// walk started
+#[allow(clippy::type_complexity)]
+pub(crate) fn descend_creating<'a, K: Hash + Eq, V, S: BuildHasher + Clone, I: IntoKeyItem<K>>(
+    mut slot: &'a mut Option<V>,
+    mut subtrees: &'a mut HashMap<K, PrefixTree<K, V, S>, S>,
+    counters: metrics::CountersRef,
+    sequence: impl IntoIterator<Item = I>,
+) -> (
+    &'a mut Option<V>,
+    &'a mut HashMap<K, PrefixTree<K, V, S>, S>,
+) {
+    for item in sequence.into_iter().map(IntoKeyItem::into_key_item) {
+        let hash_builder = subtrees.hasher().clone();
+        let subtree = subtrees.entry(item).or_insert_with(|| {
+            counters.allocation();
+            PrefixTree::with_hasher(hash_builder)
+        });
+        (slot, subtrees) = (&mut subtree.value, &mut subtree.subtrees);
+    }
+    (slot, subtrees)
 }
 
 #[cfg(test)]
@@ -207,6 +946,8 @@ mod tests {
             PrefixTree {
                 value: $value,
                 subtrees: $subtrees,
+                #[cfg(feature = "metrics")]
+                metrics: Default::default(),
             }
         };
     }
@@ -256,4 +997,377 @@ mod tests {
 
         assert_eq!(chars.as_str(), "abc");
     }
+
+    #[test]
+    fn test_key_sequences() {
+        let key = vec!['a', 'b'];
+        let mut tree = PrefixTree::new();
+
+        tree.insert(&key, 1);
+        tree.insert(key[..1].iter(), 2);
+
+        assert_eq!(tree.get_exact_match(&key), Some(&1));
+        assert_eq!(tree.get_exact_match(key[..1].iter()), Some(&2));
+        assert_eq!(tree.get_exact_match("ab".chars()), Some(&1));
+        assert_eq!(tree.get_by_shortest_prefix(['a', 'b']), Some(&2));
+    }
+
+    #[test]
+    fn test_timing_safe() {
+        let mut tree = PrefixTree::new();
+        tree.insert("ab".chars(), 1);
+        tree.insert("b".chars(), 2);
+
+        assert_eq!(tree.get_exact_match_timing_safe("ab".chars()), Some(&1));
+        assert_eq!(tree.get_exact_match_timing_safe("a".chars()), None);
+        assert_eq!(tree.get_exact_match_timing_safe("xb".chars()), None);
+        assert_eq!(tree.get_exact_match_timing_safe("abc".chars()), None);
+    }
+
+    #[test]
+    fn test_longest_prefix() {
+        let mut tree = PrefixTree::new();
+        tree.insert("a".chars(), 1);
+        tree.insert("abc".chars(), 2);
+        tree.insert("abcde".chars(), 3);
+
+        assert_eq!(tree.get_by_longest_prefix("abcdx".chars()), Some(&2));
+        assert_eq!(tree.get_by_longest_prefix("abcdef".chars()), Some(&3));
+        assert_eq!(tree.get_by_longest_prefix("x".chars()), None);
+        *tree.get_by_longest_prefix_mut("ab".chars()).unwrap() += 10;
+        assert_eq!(tree.get_exact_match("a".chars()), Some(&11));
+        assert_eq!(tree.get_by_longest_prefix_mut("".chars()), None);
+        // The sequence is read no further than the first item missing from the tree
+        let endless = "abcdx".chars().chain(std::iter::repeat('y'));
+        assert_eq!(tree.get_by_longest_prefix_mut(endless), Some(&mut 2));
+
+        assert_eq!(tree.remove_by_longest_prefix("abcdef".chars()), Some(3));
+        assert_eq!(tree.remove_by_longest_prefix("abcdef".chars()), Some(2));
+        assert_eq!(tree.remove_by_longest_prefix("x".chars()), None);
+        let mut expected = PrefixTree::new();
+        expected.insert("a".chars(), 11);
+        assert_eq!(tree, expected);
+        assert!(!tree.canonicalize());
+    }
+
+    #[test]
+    fn test_matched_len() {
+        let mut tree = PrefixTree::new();
+        tree.insert("ab".chars(), 1);
+        tree.insert("abcd".chars(), 2);
+
+        let input = "abcdef";
+        let (len, value) = tree.get_by_longest_prefix_with_len(input.chars()).unwrap();
+        assert_eq!((&input[len..], value), ("ef", &2));
+        assert_eq!(
+            tree.get_by_shortest_prefix_with_len(input.chars()),
+            Some((2, &1))
+        );
+        assert_eq!(tree.get_by_shortest_prefix_with_len("a".chars()), None);
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut tree = PrefixTree::new();
+        tree.insert("ab".chars(), 1);
+        tree.insert("abcd".chars(), 2);
+
+        assert_eq!(tree.lookup("ab".chars()), Match::Exact(&1));
+        assert_eq!(
+            tree.lookup("abc".chars()),
+            Match::Prefix {
+                value: &1,
+                matched_len: 2
+            }
+        );
+        assert_eq!(
+            tree.lookup("abcdx".chars()),
+            Match::Prefix {
+                value: &2,
+                matched_len: 4
+            }
+        );
+        assert_eq!(
+            tree.lookup("abx".chars()),
+            Match::Prefix {
+                value: &1,
+                matched_len: 2
+            }
+        );
+        assert_eq!(tree.lookup("a".chars()), Match::None);
+        assert_eq!(tree.lookup("x".chars()), Match::None);
+    }
+
+    #[test]
+    fn test_match_prefix() {
+        let mut tree = PrefixTree::new();
+        tree.insert("let".chars(), "keyword");
+        tree.insert(" ".chars(), "space");
+        tree.insert("x".chars(), "name");
+
+        let mut rest = "let x".chars();
+        let mut tokens = Vec::new();
+        while let Some((token, remainder)) = tree.match_prefix(rest.clone()) {
+            tokens.push(*token);
+            rest = remainder;
+        }
+        assert_eq!(tokens, ["keyword", "space", "name"]);
+        assert_eq!(rest.as_str(), "");
+
+        // Reading "s" and "x" tells that "let" is the longest match, so they are given back
+        tree.insert("lets go".chars(), "unused");
+        let (token, rest) = tree.match_longest_prefix("letsx!".chars()).unwrap();
+        assert_eq!(
+            (*token, rest.collect::<String>()),
+            ("keyword", "sx!".to_owned())
+        );
+        let (token, rest) = tree.match_longest_prefix("x".chars()).unwrap();
+        assert_eq!((*token, rest.count()), ("name", 0));
+        assert!(tree.match_longest_prefix("y".chars()).is_none());
+        tree.remove_exact_match("lets go".chars());
+
+        assert_eq!(
+            tree.remove_by_shortest_prefix_with_len("letter".chars()),
+            Some((3, "keyword"))
+        );
+        let endless = "xy".chars().chain(std::iter::repeat('z'));
+        assert_eq!(
+            tree.remove_by_longest_prefix_with_len(endless),
+            Some((1, "name"))
+        );
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut tree = PrefixTree::new();
+        tree.insert("ab".chars(), ());
+        tree.insert("abcd".chars(), ());
+
+        assert!(tree.contains_exact("ab".chars()));
+        assert!(!tree.contains_exact("abc".chars()));
+        assert!(tree.contains_prefix("abc".chars()));
+        assert!(!tree.contains_prefix("a".chars()));
+        assert!(tree.is_prefix("abc".chars()));
+        assert!(tree.is_prefix("".chars()));
+        assert!(!tree.is_prefix("abd".chars()));
+    }
+
+    #[test]
+    fn test_get_key_value() {
+        let mut tree = PrefixTree::new();
+        tree.insert("ab".chars(), 1);
+        tree.insert("abcd".chars(), 2);
+
+        let key = |key: &str| key.chars().collect::<Vec<_>>();
+        assert_eq!(tree.get_key_value("ab".chars()), Some((key("ab"), &1)));
+        assert_eq!(tree.get_key_value("abc".chars()), None);
+        assert_eq!(
+            tree.get_key_value_by_shortest_prefix("abcde".chars()),
+            Some((key("ab"), &1))
+        );
+        assert_eq!(
+            tree.get_key_value_by_longest_prefix("abcx".chars()),
+            Some((key("ab"), &1))
+        );
+        assert_eq!(
+            tree.get_key_value_by_longest_prefix("abcde".chars()),
+            Some((key("abcd"), &2))
+        );
+    }
+
+    #[test]
+    fn test_subtree() {
+        let mut tree = PrefixTree::new();
+        tree.insert("ns/a".chars(), 1);
+        tree.insert("ns/b".chars(), 2);
+        tree.insert("other".chars(), 3);
+
+        let namespace = tree.subtree("ns/".chars()).unwrap();
+        assert_eq!(namespace.len(), 2);
+        assert_eq!(namespace.get_exact_match("b".chars()), Some(&2));
+        assert!(tree.subtree("x".chars()).is_none());
+        assert_eq!(tree.subtree("".chars()), Some(&tree));
+    }
+
+    #[test]
+    fn test_children() {
+        let mut tree = PrefixTree::new();
+        tree.insert("File/Open".split('/'), 1);
+        tree.insert("File/Save".split('/'), 2);
+        tree.insert("File/Export/PDF".split('/'), 3);
+        tree.insert("Edit".split('/'), 4);
+
+        let mut menu: Vec<_> = tree.children(["File"]).collect();
+        menu.sort_unstable();
+        assert_eq!(
+            menu,
+            [(&"Export", None), (&"Open", Some(&1)), (&"Save", Some(&2))]
+        );
+        assert_eq!(tree.children([] as [&str; 0]).count(), 2);
+        assert_eq!(tree.children(["Edit"]).count(), 0);
+        assert_eq!(tree.children(["View"]).count(), 0);
+    }
+
+    #[test]
+    fn test_walk() {
+        let mut tree = PrefixTree::new();
+        tree.insert("abc".chars(), 1);
+
+        let walk = tree.walk("abx".chars());
+        assert_eq!(walk.node, tree.subtree("ab".chars()).unwrap());
+        assert_eq!((walk.consumed, walk.end), (2, WalkEnd::MissingChild('x')));
+        let walk = tree.walk("abc".chars());
+        assert_eq!(walk.node.value, Some(1));
+        assert_eq!((walk.consumed, walk.end), (3, WalkEnd::EndOfInput));
+        assert_eq!(tree.walk("".chars()).consumed, 0);
+    }
+
+    #[test]
+    fn test_root_value() {
+        let mut tree = PrefixTree::new();
+        tree.insert("ab".chars(), 1);
+        assert_eq!(tree.set_root_value(0), None);
+        assert_eq!(tree.get_exact_match("".chars()), Some(&0));
+        assert_eq!(tree.get_by_shortest_prefix("abc".chars()), Some(&0));
+        assert_eq!(tree.get_by_shortest_prefix("x".chars()), Some(&0));
+        assert_eq!(tree.get_by_longest_prefix("abc".chars()), Some(&1));
+        assert_eq!(tree.get_by_longest_prefix("x".chars()), Some(&0));
+
+        assert_eq!(tree.remove_by_shortest_prefix("ab".chars()), Some(0));
+        assert_eq!(tree.root_value(), None);
+        assert_eq!(tree.get_by_shortest_prefix("abc".chars()), Some(&1));
+        tree.insert("".chars(), 2);
+        assert_eq!(tree.take_root_value(), Some(2));
+        assert_eq!(tree.remove_exact_match("".chars()), None);
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn test_probe() {
+        let mut tree = PrefixTree::new();
+        let (present, value) = tree.probe("ab".chars(), || 1);
+        assert!(!present);
+        *value += 1;
+        assert_eq!(tree.probe("ab".chars(), || 10), (true, &mut 2));
+        assert_eq!(tree.get_exact_match("ab".chars()), Some(&2));
+    }
+
+    #[test]
+    fn test_replace_prefix() {
+        let mut tree = PrefixTree::new();
+        tree.insert("t1/a".chars(), 1);
+        tree.insert("t1/b".chars(), 2);
+        tree.insert("t2/a".chars(), 3);
+
+        let mut reloaded = PrefixTree::new();
+        reloaded.insert("c".chars(), 4);
+        let old = tree.replace_prefix("t1/".chars(), reloaded);
+        let mut expected_old = PrefixTree::new();
+        expected_old.insert("a".chars(), 1);
+        expected_old.insert("b".chars(), 2);
+        assert_eq!(old, expected_old);
+        assert_eq!(tree.get_exact_match("t1/c".chars()), Some(&4));
+        assert_eq!(tree.get_exact_match("t1/a".chars()), None);
+
+        tree.replace_prefix("t2/".chars(), PrefixTree::new());
+        tree.replace_prefix("t3/".chars(), PrefixTree::new());
+        let mut expected = PrefixTree::new();
+        expected.insert("t1/c".chars(), 4);
+        assert_eq!(tree, expected);
+        assert!(!tree.canonicalize());
+    }
+
+    #[test]
+    fn test_copied_slices() {
+        let mut tree = PrefixTree::new();
+
+        assert_eq!(tree.insert_copied(b"GET /", 1), None);
+        assert_eq!(tree.insert_copied(b"GET /", 2), Some(1));
+
+        assert_eq!(tree.get_exact_match_slice(b"GET /"), Some(&2));
+        assert_eq!(tree.get_exact_match_slice(b"GET"), None);
+        assert_eq!(tree.get_exact_match(b"GET /"), Some(&2));
+    }
+
+    #[test]
+    fn test_get_by_lowest_cost_prefix() {
+        let mut tree = PrefixTree::new();
+        tree.insert("a".chars(), ("a", 2));
+        tree.insert("ab".chars(), ("ab", 1));
+        tree.insert("abc".chars(), ("abc", 1));
+        tree.insert("abcd".chars(), ("abcd", 0));
+
+        let cost = |(_, cost): &(&str, u32)| *cost;
+        assert_eq!(
+            tree.get_by_lowest_cost_prefix("abcx".chars(), cost),
+            Some(&("abc", 1))
+        );
+        assert_eq!(tree.get_by_lowest_cost_prefix("x".chars(), cost), None);
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let mut tree = tree!(
+            None,
+            hashmap! {
+                'a' => tree!(None, hashmap!{
+                    'b' => tree!(None, hashmap!{}),
+                }),
+                'c' => tree!(Some(1), hashmap!{}),
+            }
+        );
+
+        assert!(tree.canonicalize());
+        assert_eq!(
+            tree,
+            tree!(None, hashmap! { 'c' => tree!(Some(1), hashmap!{}) })
+        );
+        assert!(!tree.canonicalize());
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_metrics() {
+        let mut tree = PrefixTree::new();
+
+        tree.insert("ab".chars(), 1);
+        tree.insert("ac".chars(), 2);
+        tree.get_exact_match("ab".chars());
+        tree.get_exact_match_mut("b".chars());
+        tree.get_by_shortest_prefix("acd".chars());
+
+        assert_eq!(
+            tree.metrics(),
+            Metrics {
+                lookups: 3,
+                hits: 2,
+                misses: 1,
+                node_visits: 7,
+                allocations: 3,
+            }
+        );
+
+        tree.reset_metrics();
+        assert_eq!(tree.metrics(), Metrics::default());
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_coverage_report() {
+        let mut tree = PrefixTree::new();
+        tree.insert("a".chars(), 1);
+        tree.insert("ab".chars(), 2);
+        tree.insert("b".chars(), 3);
+
+        tree.get_by_shortest_prefix("abc".chars());
+        *tree.get_exact_match_mut("ab".chars()).unwrap() += 1;
+        tree.get_exact_match("ab".chars());
+        tree.get_exact_match("x".chars());
+
+        assert_eq!(
+            tree.coverage_report(),
+            vec![(vec!['a'], 1), (vec!['a', 'b'], 2), (vec!['b'], 0)]
+        );
+    }
 }
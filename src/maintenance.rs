@@ -0,0 +1,172 @@
+use std::hash::{BuildHasher, Hash};
+use std::time::Instant;
+
+use crate::PrefixTree;
+
+/// How much work a single `PrefixTree::maintenance` call may do; the call stops as soon as either
+/// limit is reached, but always processes at least one node, so repeated calls finish the pass
+/// even with a zero node limit or a deadline in the past
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaintenanceBudget {
+    pub max_nodes: Option<usize>,
+    pub deadline: Option<Instant>,
+}
+
+impl MaintenanceBudget {
+    fn exhausted(&self, nodes: usize) -> bool {
+        self.max_nodes.map_or(false, |max_nodes| nodes >= max_nodes)
+            || self
+                .deadline
+                .map_or(false, |deadline| Instant::now() >= deadline)
+    }
+}
+
+/// The position where the previous `PrefixTree::maintenance` call stopped
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceCursor<K> {
+    /// The child keys left to process of every node on the path to the next node to process, in
+    /// reverse order, so every node's children are collected and sorted once per pass; the last
+    /// key of every level leads to the next level
+    levels: Vec<Vec<K>>,
+}
+
+impl<K> MaintenanceCursor<K> {
+    pub fn new() -> Self {
+        Self { levels: Vec::new() }
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Does a part of the work of `canonicalize`, bounded by the budget, continuing from where the
+    /// previous call with the same cursor stopped; meant to be called repeatedly from an idle
+    /// loop. Returns `true` when a full pass over the tree has been completed, after which the
+    /// cursor starts over. Nodes are processed in lexicographic order of their keys, children
+    /// before parents, so every branch that became dead during the pass is removed in it. The
+    /// children of a node are listed when the pass reaches it, so the ones added later are left
+    /// for the next pass
+    pub fn maintenance(
+        &mut self,
+        cursor: &mut MaintenanceCursor<K>,
+        budget: MaintenanceBudget,
+    ) -> bool {
+        let mut processed = 0;
+        maintain(self, 0, cursor, &budget, &mut processed).is_ok()
+    }
+}
+
+struct Paused;
+
+/// Processes the subtree at `depth` in post-order, resuming from the cursor's levels from `depth`
+/// on if there are any
+fn maintain<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone>(
+    root: &mut PrefixTree<K, V, S>,
+    depth: usize,
+    cursor: &mut MaintenanceCursor<K>,
+    budget: &MaintenanceBudget,
+    processed: &mut usize,
+) -> Result<(), Paused> {
+    if cursor.levels.len() == depth {
+        let mut keys: Vec<K> = root.subtrees.keys().cloned().collect();
+        keys.sort_unstable_by(|a, b| b.cmp(a));
+        cursor.levels.push(keys);
+    }
+    while let Some(key) = cursor.levels[depth].last() {
+        match root.subtrees.get_mut(key) {
+            Some(subtree) => maintain(subtree, depth + 1, cursor, budget, processed)?,
+            // The child was removed by hand since the previous call, along with what was left of
+            // its pass
+            None => cursor.levels.truncate(depth + 1),
+        }
+        cursor.levels[depth].pop();
+    }
+    if *processed > 0 && budget.exhausted(*processed) {
+        return Err(Paused);
+    }
+    *processed += 1;
+    root.subtrees
+        .retain(|_, subtree| subtree.value.is_some() || !subtree.subtrees.is_empty());
+    root.subtrees.shrink_to_fit();
+    cursor.levels.pop();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maintenance() {
+        let dead = || {
+            let mut dead = PrefixTree::new();
+            dead.subtrees.insert('x', PrefixTree::new());
+            dead
+        };
+        let mut tree = PrefixTree::new();
+        tree.insert("a".chars(), 1);
+        tree.insert("b".chars(), 2);
+        tree.subtrees
+            .get_mut(&'a')
+            .unwrap()
+            .subtrees
+            .insert('y', dead());
+        tree.subtrees.insert('c', dead());
+
+        let mut expected = PrefixTree::new();
+        expected.insert("a".chars(), 1);
+        expected.insert("b".chars(), 2);
+
+        let mut cursor = MaintenanceCursor::new();
+        let budget = MaintenanceBudget {
+            max_nodes: Some(2),
+            deadline: None,
+        };
+        let mut calls = 1;
+        while !tree.maintenance(&mut cursor, budget) {
+            calls += 1;
+        }
+        assert_eq!(calls, 4);
+        assert_eq!(tree, expected);
+
+        // An empty budget still makes progress, one node per call
+        tree.subtrees.insert('c', dead());
+        let budget = MaintenanceBudget {
+            max_nodes: Some(0),
+            deadline: Some(Instant::now()),
+        };
+        let mut calls = 1;
+        while !tree.maintenance(&mut cursor, budget) {
+            calls += 1;
+        }
+        assert_eq!(calls, 5);
+        assert_eq!(tree, expected);
+    }
+
+    #[test]
+    fn test_wide_root() {
+        let mut tree: PrefixTree<u32, ()> = PrefixTree::new();
+        for item in 0..100 {
+            tree.subtrees.insert(item, PrefixTree::new());
+        }
+        let mut cursor = MaintenanceCursor::new();
+        let budget = MaintenanceBudget {
+            max_nodes: Some(1),
+            deadline: None,
+        };
+        assert!(!tree.maintenance(&mut cursor, budget));
+        // The sorted children of the root are kept for the following calls, and the pass has
+        // started on the next child
+        assert_eq!(cursor.levels.len(), 2);
+        assert_eq!(cursor.levels[0].len(), 99);
+        assert_eq!(cursor.levels[0].last(), Some(&1));
+
+        // A child removed by hand between the calls is skipped
+        tree.subtrees.remove(&1);
+        let mut calls = 1;
+        while !tree.maintenance(&mut cursor, budget) {
+            calls += 1;
+        }
+        assert_eq!(calls, 99);
+        assert!(tree.subtrees.is_empty());
+        assert!(cursor.levels.is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+/// The lookups shared by the prefix map representations, the read-only ones included, so code
+/// can be written once and switch between them. Keys are passed as slices to keep the trait
+/// usable as a trait object
+pub trait PrefixMapRead<K, V> {
+    fn get_exact_match(&self, key: &[K]) -> Option<&V>;
+
+    fn get_by_shortest_prefix(&self, key: &[K]) -> Option<&V>;
+
+    /// Returns the number of stored values
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The operations shared by the mutable prefix map representations
+pub trait PrefixMapLike<K, V>: PrefixMapRead<K, V> {
+    fn insert(&mut self, key: &[K], value: V) -> Option<V>;
+
+    fn get_exact_match_mut(&mut self, key: &[K]) -> Option<&mut V>;
+
+    fn remove_exact_match(&mut self, key: &[K]) -> Option<V>;
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixMapRead<K, V> for PrefixTree<K, V, S> {
+    fn get_exact_match(&self, key: &[K]) -> Option<&V> {
+        PrefixTree::get_exact_match(self, key)
+    }
+
+    fn get_by_shortest_prefix(&self, key: &[K]) -> Option<&V> {
+        PrefixTree::get_by_shortest_prefix(self, key)
+    }
+
+    fn len(&self) -> usize {
+        PrefixTree::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        PrefixTree::is_empty(self)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher + Clone> PrefixMapLike<K, V> for PrefixTree<K, V, S> {
+    fn insert(&mut self, key: &[K], value: V) -> Option<V> {
+        PrefixTree::insert(self, key, value)
+    }
+
+    fn get_exact_match_mut(&mut self, key: &[K]) -> Option<&mut V> {
+        PrefixTree::get_exact_match_mut(self, key)
+    }
+
+    fn remove_exact_match(&mut self, key: &[K]) -> Option<V> {
+        PrefixTree::remove_exact_match(self, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CountedPrefixTree, HybridPrefixTree, OrderedPrefixTree};
+
+    use super::*;
+
+    fn route(map: &mut dyn PrefixMapLike<char, u32>) {
+        map.insert(&['a'], 1);
+        map.insert(&['a', 'b'], 2);
+        *map.get_exact_match_mut(&['a', 'b']).unwrap() += 1;
+        assert_eq!(map.get_by_shortest_prefix(&['a', 'b', 'c']), Some(&1));
+        assert_eq!(map.remove_exact_match(&['a']), Some(1));
+        assert_eq!(map.get_exact_match(&['a', 'b']), Some(&3));
+        assert_eq!(map.len(), 1);
+    }
+
+    fn lookup(map: &dyn PrefixMapRead<char, u32>) {
+        assert_eq!(map.get_exact_match(&['a', 'b']), Some(&2));
+        assert_eq!(map.get_exact_match(&['b']), None);
+        assert_eq!(map.get_by_shortest_prefix(&['a', 'b', 'c']), Some(&1));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn test_prefix_map_like() {
+        route(&mut PrefixTree::new());
+        route(&mut OrderedPrefixTree::new());
+        route(&mut CountedPrefixTree::new());
+    }
+
+    #[test]
+    fn test_prefix_map_read() {
+        let tree = || {
+            let mut tree = PrefixTree::new();
+            tree.insert("a".chars(), 1);
+            tree.insert("ab".chars(), 2);
+            tree
+        };
+        lookup(&tree());
+        lookup(&tree().freeze());
+        lookup(&tree().adapt());
+
+        let mut hybrid = HybridPrefixTree::new();
+        hybrid.insert(&['a'], 1).unwrap();
+        hybrid.insert(&['a', 'b'], 2).unwrap();
+        hybrid.freeze(&['a', 'b']);
+        lookup(&hybrid);
+    }
+}
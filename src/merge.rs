@@ -0,0 +1,161 @@
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+/// A key whose value was changed differently in both merged trees
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Conflict<K, V> {
+    pub key: Vec<K>,
+    pub base: Option<V>,
+    pub ours: Option<V>,
+    pub theirs: Option<V>,
+}
+
+impl<K: Hash + Eq + Ord + Clone, V: Clone + PartialEq, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Merges the changes made in `ours` and `theirs` relative to their common ancestor `base`.
+    /// A key changed on only one side takes that side's value; a key changed on both sides in
+    /// different ways is passed to `resolver`, whose result (`None` meaning "no value") is stored
+    /// in the merged tree. Returns the merged tree and every conflict that was encountered, in the
+    /// lexicographic order of their keys, which is also the order of the `resolver` calls
+    pub fn merge3(
+        base: &Self,
+        ours: &Self,
+        theirs: &Self,
+        mut resolver: impl FnMut(&Conflict<K, V>) -> Option<V>,
+    ) -> (Self, Vec<Conflict<K, V>>) {
+        let mut conflicts = Vec::new();
+        let merged = merge_nodes(
+            Some(base),
+            Some(ours),
+            Some(theirs),
+            &mut Vec::new(),
+            &mut resolver,
+            &mut conflicts,
+        )
+        .unwrap_or_else(|| PrefixTree::with_hasher(ours.subtrees.hasher().clone()));
+        (merged, conflicts)
+    }
+}
+
+fn merge_nodes<K: Hash + Eq + Ord + Clone, V: Clone + PartialEq, S: BuildHasher + Clone>(
+    base: Option<&PrefixTree<K, V, S>>,
+    ours: Option<&PrefixTree<K, V, S>>,
+    theirs: Option<&PrefixTree<K, V, S>>,
+    path: &mut Vec<K>,
+    resolver: &mut impl FnMut(&Conflict<K, V>) -> Option<V>,
+    conflicts: &mut Vec<Conflict<K, V>>,
+) -> Option<PrefixTree<K, V, S>> {
+    let base_value = base.and_then(|node| node.value.as_ref());
+    let our_value = ours.and_then(|node| node.value.as_ref());
+    let their_value = theirs.and_then(|node| node.value.as_ref());
+    let value = if our_value == their_value || base_value == their_value {
+        our_value.cloned()
+    } else if base_value == our_value {
+        their_value.cloned()
+    } else {
+        let conflict = Conflict {
+            key: path.clone(),
+            base: base_value.cloned(),
+            ours: our_value.cloned(),
+            theirs: their_value.cloned(),
+        };
+        let resolved = resolver(&conflict);
+        conflicts.push(conflict);
+        resolved
+    };
+    // At least one of the nodes is there, since the key of the merged node came from one of them
+    let hash_builder = [ours, theirs, base]
+        .into_iter()
+        .flatten()
+        .map(|node| node.subtrees.hasher().clone())
+        .next()
+        .unwrap();
+    let mut merged = PrefixTree::with_hasher(hash_builder);
+    merged.value = value;
+    // The children are merged in the order of their items, so the conflicts come in the order of
+    // their keys
+    let mut keys: Vec<_> = [ours, theirs, base]
+        .into_iter()
+        .flatten()
+        .flat_map(|node| node.subtrees.keys())
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+    for key in keys {
+        path.push(key.clone());
+        let subtree = merge_nodes(
+            base.and_then(|node| node.subtrees.get(key)),
+            ours.and_then(|node| node.subtrees.get(key)),
+            theirs.and_then(|node| node.subtrees.get(key)),
+            path,
+            resolver,
+            conflicts,
+        );
+        path.pop();
+        if let Some(subtree) = subtree {
+            merged.subtrees.insert(key.clone(), subtree);
+        }
+    }
+    if merged.value.is_none() && merged.subtrees.is_empty() {
+        None
+    } else {
+        Some(merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge3() {
+        let mut base = PrefixTree::new();
+        base.insert("a".chars(), 1);
+        base.insert("ab".chars(), 2);
+        base.insert("abc".chars(), 3);
+
+        let mut ours = PrefixTree::new();
+        ours.insert("a".chars(), 10);
+        ours.insert("ab".chars(), 20);
+        ours.insert("abc".chars(), 3);
+
+        let mut theirs = PrefixTree::new();
+        theirs.insert("a".chars(), 1);
+        theirs.insert("ab".chars(), 21);
+        theirs.insert("x".chars(), 4);
+        for key in ["b", "c", "d", "e"] {
+            ours.insert(key.chars(), 5);
+            theirs.insert(key.chars(), 6);
+        }
+
+        let mut resolved = Vec::new();
+        let (merged, conflicts) = PrefixTree::merge3(&base, &ours, &theirs, |conflict| {
+            resolved.push(conflict.key.clone());
+            conflict.theirs
+        });
+
+        let mut expected = PrefixTree::new();
+        expected.insert("a".chars(), 10);
+        expected.insert("ab".chars(), 21);
+        expected.insert("x".chars(), 4);
+        for key in ["b", "c", "d", "e"] {
+            expected.insert(key.chars(), 6);
+        }
+        assert_eq!(merged, expected);
+        assert_eq!(
+            conflicts[0],
+            Conflict {
+                key: vec!['a', 'b'],
+                base: Some(2),
+                ours: Some(20),
+                theirs: Some(21),
+            }
+        );
+        let keys: Vec<_> = conflicts.into_iter().map(|conflict| conflict.key).collect();
+        assert_eq!(
+            keys,
+            [vec!['a', 'b'], vec!['b'], vec!['c'], vec!['d'], vec!['e']]
+        );
+        assert_eq!(resolved, keys);
+    }
+}
@@ -0,0 +1,119 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::time::SystemTime;
+
+use crate::{IntoKeyItem, PrefixTree};
+
+/// What is known about how an entry got into a `MetadataPrefixTree`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata<S> {
+    /// When the entry was inserted
+    pub created_at: SystemTime,
+    /// How many insertions had been made into the tree before this one
+    pub insertion: u64,
+    /// Where the entry came from, as told to `insert` (e.g. a config file name)
+    pub source: S,
+}
+
+/// A prefix tree that records `Metadata` for every entry on insertion, without it being part of
+/// the values
+#[derive(Debug)]
+pub struct MetadataPrefixTree<K: Hash + Eq, V, S> {
+    tree: PrefixTree<K, (V, Metadata<S>)>,
+    insertions: u64,
+}
+
+impl<K: Hash + Eq, V, S> Default for MetadataPrefixTree<K, V, S> {
+    fn default() -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            insertions: 0,
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S> MetadataPrefixTree<K, V, S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same as `PrefixTree::insert`; the replaced entry is returned with its metadata
+    pub fn insert<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        value: V,
+        source: S,
+    ) -> Option<(V, Metadata<S>)> {
+        let metadata = Metadata {
+            created_at: SystemTime::now(),
+            insertion: self.insertions,
+            source,
+        };
+        self.insertions += 1;
+        self.tree.insert(sequence, (value, metadata))
+    }
+
+    pub fn get_exact_match<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(&V, &Metadata<S>)> {
+        self.tree
+            .get_exact_match(sequence)
+            .map(|(value, metadata)| (value, metadata))
+    }
+
+    pub fn get_by_shortest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(&V, &Metadata<S>)> {
+        self.tree
+            .get_by_shortest_prefix(sequence)
+            .map(|(value, metadata)| (value, metadata))
+    }
+
+    /// Returns a mutable reference to the value at the exact key; the metadata stays the same
+    pub fn get_exact_match_mut<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&mut V> {
+        self.tree
+            .get_exact_match_mut(sequence)
+            .map(|(value, _)| value)
+    }
+
+    pub fn remove_exact_match<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(V, Metadata<S>)> {
+        self.tree.remove_exact_match(sequence)
+    }
+
+    pub fn tree(&self) -> &PrefixTree<K, (V, Metadata<S>)> {
+        &self.tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_prefix_tree() {
+        let before = SystemTime::now();
+        let mut routes = MetadataPrefixTree::new();
+        routes.insert("ab".chars(), 1, "base.conf");
+        routes.insert("b".chars(), 2, "extra.conf");
+        let (_, replaced) = routes.insert("ab".chars(), 3, "override.conf").unwrap();
+        assert_eq!(replaced.source, "base.conf");
+
+        let (value, metadata) = routes.get_by_shortest_prefix("abc".chars()).unwrap();
+        assert_eq!(*value, 3);
+        assert_eq!(metadata.insertion, 2);
+        assert_eq!(metadata.source, "override.conf");
+        assert!(metadata.created_at >= before);
+
+        *routes.get_exact_match_mut("b".chars()).unwrap() += 1;
+        let (value, metadata) = routes.get_exact_match("b".chars()).unwrap();
+        assert_eq!((*value, metadata.insertion), (3, 1));
+    }
+}
@@ -0,0 +1,201 @@
+//! Operation counters, only collected when the `metrics` feature is enabled. Without the feature
+//! every type here is zero-sized and every method is a no-op, so the instrumentation in the tree
+//! methods compiles away
+
+#[cfg(feature = "metrics")]
+use std::hash::{BuildHasher, Hash};
+#[cfg(feature = "metrics")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "metrics")]
+use crate::PrefixTree;
+
+/// A snapshot of the operations performed on a tree since its creation or the last
+/// `PrefixTree::reset_metrics` call
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    /// How many `get_*` lookups were made
+    pub lookups: usize,
+    /// How many lookups found a value
+    pub hits: usize,
+    /// How many lookups found nothing
+    pub misses: usize,
+    /// How many nodes the lookups went through in total
+    pub node_visits: usize,
+    /// How many nodes were created by insertions
+    pub allocations: usize,
+}
+
+/// The counters stored in every node; only the ones in the node that a method was called on are
+/// updated. They are ignored when comparing trees
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    lookups: AtomicUsize,
+    hits: AtomicUsize,
+    node_visits: AtomicUsize,
+    allocations: AtomicUsize,
+    /// How many lookups (made through any node) found the value of this node
+    entry_hits: AtomicUsize,
+}
+
+#[cfg(feature = "metrics")]
+impl PartialEq for Counters {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Eq for Counters {}
+
+#[cfg(feature = "metrics")]
+impl Counters {
+    pub(crate) fn snapshot(&self) -> Metrics {
+        let lookups = self.lookups.load(Ordering::Relaxed);
+        let hits = self.hits.load(Ordering::Relaxed);
+        Metrics {
+            lookups,
+            hits,
+            misses: lookups - hits,
+            node_visits: self.node_visits.load(Ordering::Relaxed),
+            allocations: self.allocations.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.lookups.store(0, Ordering::Relaxed);
+        self.hits.store(0, Ordering::Relaxed);
+        self.node_visits.store(0, Ordering::Relaxed);
+        self.allocations.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns every entry of the tree with the number of lookups that have found it since it was
+    /// created, in lexicographic order of the keys; entries with zero hits are rules that never
+    /// fire. Unlike `metrics`, this counts the lookups made through the subtrees as well
+    pub fn coverage_report(&self) -> Vec<(Vec<K>, usize)> {
+        let mut report = Vec::new();
+        collect_coverage(self, &mut Vec::new(), &mut report);
+        report
+    }
+}
+
+#[cfg(feature = "metrics")]
+fn collect_coverage<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone>(
+    root: &PrefixTree<K, V, S>,
+    path: &mut Vec<K>,
+    report: &mut Vec<(Vec<K>, usize)>,
+) {
+    if root.value.is_some() {
+        let hits = root.metrics.entry_hits.load(Ordering::Relaxed);
+        report.push((path.clone(), hits));
+    }
+    for (key, subtree) in root.sorted_subtrees() {
+        path.push(key.clone());
+        collect_coverage(subtree, path, report);
+        path.pop();
+    }
+}
+
+/// A handle to the counters of a tree, obtained with `counters!(tree.metrics)`. The macro takes
+/// the field (rather than the tree) so that the handle only borrows the counters and the rest of
+/// the tree stays available for mutation
+#[derive(Clone, Copy)]
+pub(crate) struct CountersRef<'a> {
+    #[cfg(feature = "metrics")]
+    counters: &'a Counters,
+    #[cfg(not(feature = "metrics"))]
+    counters: std::marker::PhantomData<&'a ()>,
+}
+
+#[cfg(feature = "metrics")]
+macro_rules! counters {
+    ($counters:expr) => {
+        $crate::metrics::CountersRef::new(&$counters)
+    };
+}
+
+#[cfg(not(feature = "metrics"))]
+macro_rules! counters {
+    ($counters:expr) => {
+        $crate::metrics::CountersRef::new()
+    };
+}
+
+pub(crate) use counters;
+
+impl<'a> CountersRef<'a> {
+    #[cfg(feature = "metrics")]
+    pub(crate) fn new(counters: &'a Counters) -> Self {
+        Self { counters }
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    pub(crate) fn new() -> Self {
+        Self {
+            counters: std::marker::PhantomData,
+        }
+    }
+
+    /// Starts counting a lookup; it is recorded when the probe is dropped
+    pub(crate) fn probe(self) -> Probe<'a> {
+        Probe {
+            counters: self,
+            #[cfg(feature = "metrics")]
+            node_visits: 0,
+            #[cfg(feature = "metrics")]
+            hit: false,
+        }
+    }
+
+    pub(crate) fn allocation(self) {
+        #[cfg(feature = "metrics")]
+        self.counters.allocations.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) struct Probe<'a> {
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    counters: CountersRef<'a>,
+    #[cfg(feature = "metrics")]
+    node_visits: usize,
+    #[cfg(feature = "metrics")]
+    hit: bool,
+}
+
+impl Probe<'_> {
+    pub(crate) fn visit(&mut self) {
+        #[cfg(feature = "metrics")]
+        {
+            self.node_visits += 1;
+        }
+    }
+
+    /// Marks the lookup as successful; `entry` are the counters of the node whose value was found
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    pub(crate) fn hit(&mut self, entry: CountersRef) {
+        #[cfg(feature = "metrics")]
+        {
+            self.hit = true;
+            entry.counters.entry_hits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        let counters = self.counters.counters;
+        counters.lookups.fetch_add(1, Ordering::Relaxed);
+        counters
+            .hits
+            .fetch_add(self.hit as usize, Ordering::Relaxed);
+        counters
+            .node_visits
+            .fetch_add(self.node_visits, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use crate::PrefixTree;
+
+/// Which lookup missed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Lookup {
+    Exact,
+    Prefix,
+}
+
+/// A prefix tree that remembers up to `capacity` recent lookups that found nothing, so repeating
+/// them costs one hash map lookup instead of a walk down the tree. The misses are looked up by
+/// the hashes of their keys (the keys are compared too, so a hash collision can't produce a wrong
+/// answer). Inserting a key forgets the misses it could have turned into hits: the ones whose keys
+/// start with the inserted key
+#[derive(Debug)]
+pub struct NegativeCacheTree<K: Hash + Eq, V> {
+    tree: PrefixTree<K, V>,
+    misses: Misses<K>,
+}
+
+#[derive(Debug)]
+struct Misses<K> {
+    keys: HashMap<u64, (Lookup, Vec<K>)>,
+    /// The hashes of the misses, the oldest first
+    order: VecDeque<u64>,
+    capacity: usize,
+    hash_builder: RandomState,
+}
+
+impl<K: Hash + Eq + Clone, V> NegativeCacheTree<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            misses: Misses {
+                keys: HashMap::new(),
+                order: VecDeque::new(),
+                capacity,
+                hash_builder: RandomState::new(),
+            },
+        }
+    }
+
+    pub fn insert(&mut self, key: &[K], value: V) -> Option<V> {
+        self.misses.forget_under(key);
+        self.tree.insert(key, value)
+    }
+
+    /// Same as `PrefixTree::get_exact_match`; a miss is remembered
+    pub fn get_exact_match(&mut self, key: &[K]) -> Option<&V> {
+        let hash = self.misses.hash(Lookup::Exact, key);
+        if self.misses.contains(hash, Lookup::Exact, key) {
+            return None;
+        }
+        let found = self.tree.get_exact_match(key);
+        if found.is_none() {
+            self.misses.remember(hash, Lookup::Exact, key);
+        }
+        found
+    }
+
+    /// Same as `PrefixTree::get_by_shortest_prefix`; a miss is remembered
+    pub fn get_by_shortest_prefix(&mut self, key: &[K]) -> Option<&V> {
+        let hash = self.misses.hash(Lookup::Prefix, key);
+        if self.misses.contains(hash, Lookup::Prefix, key) {
+            return None;
+        }
+        let found = self.tree.get_by_shortest_prefix(key);
+        if found.is_none() {
+            self.misses.remember(hash, Lookup::Prefix, key);
+        }
+        found
+    }
+
+    /// Same as `PrefixTree::remove_exact_match` (removing can't turn a miss into a hit, so the
+    /// misses are kept)
+    pub fn remove_exact_match(&mut self, key: &[K]) -> Option<V> {
+        self.tree.remove_exact_match(key)
+    }
+
+    /// Returns the number of remembered misses
+    pub fn cached_misses(&self) -> usize {
+        self.misses.keys.len()
+    }
+
+    pub fn tree(&self) -> &PrefixTree<K, V> {
+        &self.tree
+    }
+}
+
+impl<K: Hash + Eq + Clone> Misses<K> {
+    fn hash(&self, lookup: Lookup, key: &[K]) -> u64 {
+        let mut hasher = self.hash_builder.build_hasher();
+        (lookup, key).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn contains(&self, hash: u64, lookup: Lookup, key: &[K]) -> bool {
+        self.keys
+            .get(&hash)
+            .map_or(false, |(missed_lookup, missed)| {
+                *missed_lookup == lookup && missed[..] == *key
+            })
+    }
+
+    fn remember(&mut self, hash: u64, lookup: Lookup, key: &[K]) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.keys.insert(hash, (lookup, key.to_vec())).is_none() {
+            self.order.push_back(hash);
+        }
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+    }
+
+    /// Forgets the misses whose keys start with the prefix
+    fn forget_under(&mut self, prefix: &[K]) {
+        self.keys
+            .retain(|_, (_, missed)| !missed.starts_with(prefix));
+        let keys = &self.keys;
+        self.order.retain(|hash| keys.contains_key(hash));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> Vec<char> {
+        key.chars().collect()
+    }
+
+    #[test]
+    fn test_negative_cache() {
+        let mut tree = NegativeCacheTree::new(2);
+        tree.insert(&key("ab"), 1);
+
+        assert_eq!(tree.get_exact_match(&key("abc")), None);
+        assert_eq!(tree.get_by_shortest_prefix(&key("xyz")), None);
+        assert_eq!(tree.get_by_shortest_prefix(&key("xyz")), None);
+        assert_eq!(tree.cached_misses(), 2);
+        assert_eq!(tree.get_exact_match(&key("q")), None);
+        assert_eq!(tree.cached_misses(), 2);
+
+        tree.insert(&key("x"), 2);
+        assert_eq!(tree.cached_misses(), 1);
+        assert_eq!(tree.get_by_shortest_prefix(&key("xyz")), Some(&2));
+        assert_eq!(tree.get_exact_match(&key("ab")), Some(&1));
+    }
+}
@@ -0,0 +1,97 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+use crate::{IntoKeyItem, PrefixTree};
+
+/// A prefix tree that normalizes every key item (e.g. folds its case) before storing or looking it
+/// up, while remembering the original, un-normalized key of every entry so it can be shown back
+/// to the user
+#[derive(Debug)]
+pub struct NormalizedPrefixTree<K: Hash + Eq, V, N> {
+    tree: PrefixTree<K, (Vec<K>, V)>,
+    normalize: N,
+}
+
+impl<K: Hash + Eq + Clone, V, N: Fn(&K) -> K> NormalizedPrefixTree<K, V, N> {
+    pub fn new(normalize: N) -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            normalize,
+        }
+    }
+
+    /// Inserts the value at the normalized key, remembering the key as it was given; returns the
+    /// previous original key and value if the normalized key was already present
+    pub fn insert<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        value: V,
+    ) -> Option<(Vec<K>, V)> {
+        let original: Vec<K> = sequence
+            .into_iter()
+            .map(IntoKeyItem::into_key_item)
+            .collect();
+        let normalized: Vec<K> = original.iter().map(&self.normalize).collect();
+        self.tree.insert(normalized, (original, value))
+    }
+
+    /// Same as `PrefixTree::get_exact_match`, but also returns the original key of the entry
+    pub fn get_exact_match<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(&[K], &V)> {
+        let normalize = &self.normalize;
+        self.tree
+            .get_exact_match(sequence.into_iter().map(|item| normalize(item.borrow())))
+            .map(|(original, value)| (&original[..], value))
+    }
+
+    /// Same as `PrefixTree::get_by_shortest_prefix`, but also returns the original key of the entry
+    pub fn get_by_shortest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(&[K], &V)> {
+        let normalize = &self.normalize;
+        self.tree
+            .get_by_shortest_prefix(sequence.into_iter().map(|item| normalize(item.borrow())))
+            .map(|(original, value)| (&original[..], value))
+    }
+
+    /// Same as `PrefixTree::remove_exact_match`, but also returns the original key of the entry
+    pub fn remove_exact_match<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<(Vec<K>, V)> {
+        let normalize = &self.normalize;
+        self.tree
+            .remove_exact_match(sequence.into_iter().map(|item| normalize(item.borrow())))
+    }
+
+    /// Returns the underlying tree, keyed by the normalized keys
+    pub fn tree(&self) -> &PrefixTree<K, (Vec<K>, V)> {
+        &self.tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized_prefix_tree() {
+        let mut tree = NormalizedPrefixTree::new(char::to_ascii_lowercase);
+        tree.insert("iPhone".chars(), 1);
+
+        let (original, value) = tree.get_exact_match("IPHONE".chars()).unwrap();
+        assert_eq!(original.iter().collect::<String>(), "iPhone");
+        assert_eq!(value, &1);
+
+        let (original, _) = tree.insert("IPhone".chars(), 2).unwrap();
+        assert_eq!(original.iter().collect::<String>(), "iPhone");
+        assert_eq!(
+            tree.get_by_shortest_prefix("iphone 15".chars())
+                .map(|(_, value)| value),
+            Some(&2)
+        );
+    }
+}
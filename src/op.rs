@@ -0,0 +1,76 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+/// A recorded mutation of a prefix tree
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Op<K, V> {
+    Insert(Vec<K>, V),
+    RemoveExactMatch(Vec<K>),
+    RemoveByShortestPrefix(Vec<K>),
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Applies the operation to the tree; returns the value that was replaced or removed
+    pub fn apply(&mut self, op: Op<K, V>) -> Option<V> {
+        match op {
+            Op::Insert(key, value) => self.insert(key, value),
+            Op::RemoveExactMatch(key) => self.remove_exact_match(key),
+            Op::RemoveByShortestPrefix(key) => self.remove_by_shortest_prefix(key),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> PrefixTree<K, V> {
+    /// Builds a tree by applying the operations in order to an empty tree. Replaying the same log
+    /// always produces trees that compare equal, regardless of the (random) internal order in
+    /// which children are stored
+    pub fn replay(ops: impl IntoIterator<Item = Op<K, V>>) -> Self {
+        Self::replay_with_hasher(ops, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Same as `replay`, but the children maps of the tree use the hash builder
+    pub fn replay_with_hasher(ops: impl IntoIterator<Item = Op<K, V>>, hash_builder: S) -> Self {
+        let mut tree = Self::with_hasher(hash_builder);
+        for op in ops {
+            tree.apply(op);
+        }
+        tree
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone, V: Clone, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns the canonical log of the tree: one `Op::Insert` per entry, in lexicographic order
+    /// of the keys. Two equal trees always produce identical logs, and replaying the log produces
+    /// an equal tree
+    pub fn to_ops(&self) -> Vec<Op<K, V>> {
+        self.iter_sorted()
+            .map(|(key, value)| Op::Insert(key, value.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay() {
+        let log = vec![
+            Op::Insert(vec!['b'], 1),
+            Op::Insert(vec!['a', 'b'], 2),
+            Op::Insert(vec!['a'], 3),
+            Op::RemoveByShortestPrefix(vec!['a', 'b']),
+        ];
+        let tree = PrefixTree::replay(log.clone());
+        assert_eq!(tree, PrefixTree::replay(log));
+        assert_eq!(
+            tree.to_ops(),
+            vec![Op::Insert(vec!['a', 'b'], 2), Op::Insert(vec!['b'], 1)]
+        );
+        assert_eq!(PrefixTree::replay(tree.to_ops()), tree);
+    }
+}
@@ -0,0 +1,376 @@
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::ops::{Bound, RangeBounds};
+
+use crate::{IntoKeyItem, PrefixMapLike, PrefixMapRead};
+
+/// A prefix tree whose children are kept in `BTreeMap`s, so the entries are always in
+/// lexicographic order of their keys and some queries can use that order: `first`, `last` and
+/// `range`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderedPrefixTree<K: Ord, V> {
+    pub value: Option<V>,
+    pub subtrees: BTreeMap<K, OrderedPrefixTree<K, V>>,
+}
+
+impl<K: Ord, V> Default for OrderedPrefixTree<K, V> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            subtrees: BTreeMap::new(),
+        }
+    }
+}
+
+impl<K: Ord, V> OrderedPrefixTree<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same as `PrefixTree::insert`
+    pub fn insert<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        value: V,
+    ) -> Option<V> {
+        let mut root = self;
+        for item in sequence {
+            root = root.subtrees.entry(item.into_key_item()).or_default();
+        }
+        root.value.replace(value)
+    }
+
+    pub fn get_exact_match<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        let mut root = self;
+        for item in sequence {
+            root = root.subtrees.get(item.borrow())?;
+        }
+        root.value.as_ref()
+    }
+
+    pub fn get_exact_match_mut<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&mut V> {
+        let mut root = self;
+        for item in sequence {
+            root = root.subtrees.get_mut(item.borrow())?;
+        }
+        root.value.as_mut()
+    }
+
+    /// Same as `PrefixTree::get_by_shortest_prefix`
+    pub fn get_by_shortest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        let mut sequence = sequence.into_iter();
+        let mut root = self;
+        loop {
+            if let Some(value) = &root.value {
+                return Some(value);
+            }
+            root = root.subtrees.get(sequence.next()?.borrow())?;
+        }
+    }
+
+    /// Same as `PrefixTree::remove_exact_match`: removes the branches left without values
+    pub fn remove_exact_match<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<V> {
+        remove(self, &mut sequence.into_iter())
+    }
+
+    /// Same as `PrefixTree::canonicalize`: removes branches that hold no values, left by editing
+    /// the public fields by hand. The children are kept in order by the `BTreeMap`s and take no
+    /// spare capacity, so there is nothing else to do. Returns `true` if any branches were removed
+    pub fn canonicalize(&mut self) -> bool {
+        let mut changed = false;
+        self.subtrees.retain(|_, subtree| {
+            changed |= subtree.canonicalize();
+            let dead = subtree.value.is_none() && subtree.subtrees.is_empty();
+            changed |= dead;
+            !dead
+        });
+        changed
+    }
+
+    /// Returns the number of values stored in the tree; this walks the whole tree
+    pub fn len(&self) -> usize {
+        self.value.iter().count() + self.subtrees.values().map(Self::len).sum::<usize>()
+    }
+
+    /// Returns `true` if the tree stores no values
+    pub fn is_empty(&self) -> bool {
+        self.value.is_none() && self.subtrees.values().all(Self::is_empty)
+    }
+}
+
+fn remove<K: Ord, V, I: Borrow<K>>(
+    root: &mut OrderedPrefixTree<K, V>,
+    sequence: &mut impl Iterator<Item = I>,
+) -> Option<V> {
+    let item = match sequence.next() {
+        Some(item) => item,
+        None => return root.value.take(),
+    };
+    let subtree = root.subtrees.get_mut(item.borrow())?;
+    let removed = remove(subtree, sequence);
+    if subtree.value.is_none() && subtree.subtrees.is_empty() {
+        root.subtrees.remove(item.borrow());
+    }
+    removed
+}
+
+impl<K: Ord + Clone, V> OrderedPrefixTree<K, V> {
+    /// Returns the entry with the lexicographically smallest key
+    pub fn first(&self) -> Option<(Vec<K>, &V)> {
+        let mut key = Vec::new();
+        let mut root = self;
+        loop {
+            if let Some(value) = &root.value {
+                return Some((key, value));
+            }
+            let (item, subtree) = root.subtrees.iter().next()?;
+            key.push(item.clone());
+            root = subtree;
+        }
+    }
+
+    /// Returns the entry with the lexicographically largest key
+    pub fn last(&self) -> Option<(Vec<K>, &V)> {
+        // The deepest value on the rightmost path (the path always ends at a value unless the
+        // public fields were edited by hand)
+        let mut key = Vec::new();
+        let mut root = self;
+        let mut found = root.value.as_ref().map(|value| (0, value));
+        while let Some((item, subtree)) = root.subtrees.iter().next_back() {
+            key.push(item.clone());
+            root = subtree;
+            if let Some(value) = &root.value {
+                found = Some((key.len(), value));
+            }
+        }
+        let (len, value) = found?;
+        key.truncate(len);
+        Some((key, value))
+    }
+
+    /// Returns the entries whose keys are in the range, in lexicographic order of the keys (so
+    /// `range(a..b)` gives the keys that are at least `a` and less than `b`, where every key is
+    /// after its prefixes). The branches entirely outside of the range are skipped
+    pub fn range(&self, range: impl RangeBounds<Vec<K>>) -> Vec<(Vec<K>, &V)> {
+        let mut entries = Vec::new();
+        collect_range(self, &range, &mut Vec::new(), &mut entries);
+        entries
+    }
+}
+
+impl<K: Ord + Clone, V> OrderedPrefixTree<K, V> {
+    /// Returns the entry with the lexicographically smallest key after the sequence (the
+    /// sequence itself doesn't have to be a key)
+    pub fn next_key_after(&self, sequence: &[K]) -> Option<(Vec<K>, &V)> {
+        let nodes = self.path_nodes(sequence);
+        if let Some(node) = nodes.get(sequence.len()) {
+            // The extensions of the sequence come right after it
+            if let Some((item, subtree)) = node.subtrees.iter().next() {
+                return Some(join(sequence, item, subtree.first()?));
+            }
+        }
+        // Otherwise the closest key branches off the sequence as deep as possible
+        let reached = nodes.len().min(sequence.len());
+        for (depth, node) in nodes[..reached].iter().enumerate().rev() {
+            let after = (Bound::Excluded(&sequence[depth]), Bound::Unbounded);
+            if let Some((item, subtree)) = node.subtrees.range(after).next() {
+                return Some(join(&sequence[..depth], item, subtree.first()?));
+            }
+        }
+        None
+    }
+
+    /// Returns the entry with the lexicographically largest key before the sequence (the
+    /// sequence itself doesn't have to be a key)
+    pub fn prev_key_before(&self, sequence: &[K]) -> Option<(Vec<K>, &V)> {
+        let nodes = self.path_nodes(sequence);
+        // The keys at and under the node of the whole sequence don't come before it
+        let reached = nodes.len().min(sequence.len());
+        for (depth, node) in nodes[..reached].iter().enumerate().rev() {
+            // A key branching off the sequence here comes after the prefix that is a key itself
+            let before = (Bound::Unbounded, Bound::Excluded(&sequence[depth]));
+            if let Some((item, subtree)) = node.subtrees.range(before).next_back() {
+                return Some(join(&sequence[..depth], item, subtree.last()?));
+            }
+            if let Some(value) = &node.value {
+                return Some((sequence[..depth].to_vec(), value));
+            }
+        }
+        None
+    }
+
+    /// Returns the nodes at the prefixes of the sequence that are in the tree, the root first
+    fn path_nodes(&self, sequence: &[K]) -> Vec<&Self> {
+        let mut nodes = vec![self];
+        for item in sequence {
+            match nodes[nodes.len() - 1].subtrees.get(item) {
+                Some(subtree) => nodes.push(subtree),
+                None => break,
+            }
+        }
+        nodes
+    }
+}
+
+/// Builds an entry found under the child of the node at the prefix
+fn join<'a, K: Clone, V>(
+    prefix: &[K],
+    item: &K,
+    (suffix, value): (Vec<K>, &'a V),
+) -> (Vec<K>, &'a V) {
+    let mut key = prefix.to_vec();
+    key.push(item.clone());
+    key.extend(suffix);
+    (key, value)
+}
+
+/// Collects the entries in the range; returns `false` once a key after the range is reached
+fn collect_range<'a, K: Ord + Clone, V>(
+    root: &'a OrderedPrefixTree<K, V>,
+    range: &impl RangeBounds<Vec<K>>,
+    path: &mut Vec<K>,
+    entries: &mut Vec<(Vec<K>, &'a V)>,
+) -> bool {
+    // All the keys under the node start with `path`, so they come at or after it
+    let past_end = match range.end_bound() {
+        Bound::Included(end) => *path > *end,
+        Bound::Excluded(end) => *path >= *end,
+        Bound::Unbounded => false,
+    };
+    if past_end {
+        return false;
+    }
+    // ...and they all come before the start if `path` does and isn't a prefix of the start
+    let before_start = match range.start_bound() {
+        Bound::Included(start) | Bound::Excluded(start) => {
+            *path < *start && !start.starts_with(path)
+        }
+        Bound::Unbounded => false,
+    };
+    if before_start {
+        return true;
+    }
+    if let Some(value) = &root.value {
+        if range.contains(&*path) {
+            entries.push((path.clone(), value));
+        }
+    }
+    for (item, subtree) in &root.subtrees {
+        path.push(item.clone());
+        let more = collect_range(subtree, range, path, entries);
+        path.pop();
+        if !more {
+            return false;
+        }
+    }
+    true
+}
+
+impl<K: Ord, V> PrefixMapRead<K, V> for OrderedPrefixTree<K, V> {
+    fn get_exact_match(&self, key: &[K]) -> Option<&V> {
+        OrderedPrefixTree::get_exact_match(self, key)
+    }
+
+    fn get_by_shortest_prefix(&self, key: &[K]) -> Option<&V> {
+        OrderedPrefixTree::get_by_shortest_prefix(self, key)
+    }
+
+    fn len(&self) -> usize {
+        OrderedPrefixTree::len(self)
+    }
+}
+
+impl<K: Ord + Clone, V> PrefixMapLike<K, V> for OrderedPrefixTree<K, V> {
+    fn insert(&mut self, key: &[K], value: V) -> Option<V> {
+        OrderedPrefixTree::insert(self, key, value)
+    }
+
+    fn get_exact_match_mut(&mut self, key: &[K]) -> Option<&mut V> {
+        OrderedPrefixTree::get_exact_match_mut(self, key)
+    }
+
+    fn remove_exact_match(&mut self, key: &[K]) -> Option<V> {
+        OrderedPrefixTree::remove_exact_match(self, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> Vec<char> {
+        key.chars().collect()
+    }
+
+    fn keys(entries: Vec<(Vec<char>, &usize)>) -> Vec<String> {
+        entries
+            .into_iter()
+            .map(|(key, _)| key.into_iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_ordered_prefix_tree() {
+        let mut tree = OrderedPrefixTree::new();
+        for (index, word) in ["b", "ab", "abc", "ba", "c", "a"].into_iter().enumerate() {
+            tree.insert(word.chars(), index);
+        }
+
+        assert_eq!(tree.first(), Some((key("a"), &5)));
+        assert_eq!(tree.last(), Some((key("c"), &4)));
+        assert_eq!(keys(tree.range(key("ab")..key("ba"))), ["ab", "abc", "b"]);
+        assert_eq!(keys(tree.range(key("abd")..=key("ba"))), ["b", "ba"]);
+        assert_eq!(keys(tree.range(..key("ab"))), ["a"]);
+        assert_eq!(tree.range(key("ab")..).len(), 5);
+
+        assert_eq!(tree.remove_exact_match("abc".chars()), Some(2));
+        assert_eq!(tree.remove_exact_match("c".chars()), Some(4));
+        assert_eq!(tree.last(), Some((key("ba"), &3)));
+        assert_eq!(PrefixMapRead::len(&tree), 4);
+        assert_eq!(tree.get_by_shortest_prefix("abx".chars()), Some(&5));
+
+        tree.subtrees
+            .entry('x')
+            .or_default()
+            .subtrees
+            .insert('y', OrderedPrefixTree::new());
+        assert!(tree.canonicalize());
+        assert!(!tree.subtrees.contains_key(&'x'));
+        assert!(!tree.canonicalize());
+        assert_eq!(tree.len(), 4);
+    }
+
+    #[test]
+    fn test_adjacent_keys() {
+        let mut tree = OrderedPrefixTree::new();
+        for (index, word) in ["b", "ab", "abc", "ba", "c", "a"].into_iter().enumerate() {
+            tree.insert(word.chars(), index);
+        }
+
+        let next = |word: &str| tree.next_key_after(&key(word)).map(|(key, _)| key);
+        let prev = |word: &str| tree.prev_key_before(&key(word)).map(|(key, _)| key);
+        assert_eq!(next(""), Some(key("a")));
+        assert_eq!(next("a"), Some(key("ab")));
+        assert_eq!(next("abc"), Some(key("b")));
+        assert_eq!(next("abd"), Some(key("b")));
+        assert_eq!(next("bb"), Some(key("c")));
+        assert_eq!(next("c"), None);
+        assert_eq!(prev("a"), None);
+        assert_eq!(prev("ab"), Some(key("a")));
+        assert_eq!(prev("b"), Some(key("abc")));
+        assert_eq!(prev("bab"), Some(key("ba")));
+        assert_eq!(prev("zz"), Some(key("c")));
+    }
+}
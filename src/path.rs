@@ -0,0 +1,161 @@
+use crate::PrefixTree;
+
+/// How `PathPrefixTree` splits paths into components; the defaults split Unix paths as they are
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathOptions {
+    /// Compare the components case-insensitively
+    pub case_insensitive: bool,
+    /// Accept `\` as a separator and treat a drive (`C:`) or a UNC share (`\\server\share`) as a
+    /// single root component
+    pub windows: bool,
+    /// Skip `.` components and let `..` remove the previous component (but never the root one)
+    pub resolve_dots: bool,
+}
+
+impl PathOptions {
+    /// The options that match how Windows compares paths
+    pub fn windows() -> Self {
+        Self {
+            case_insensitive: true,
+            windows: true,
+            resolve_dots: true,
+        }
+    }
+
+    /// The options that match how the current platform compares paths
+    pub fn native() -> Self {
+        if cfg!(windows) {
+            Self::windows()
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Splits the path into the components under which it is stored in the tree; separators are
+    /// collapsed, so `/usr//bin/` and `usr/bin` have the same components
+    pub fn components(&self, path: &str) -> Vec<String> {
+        let case = |component: &str| {
+            if self.case_insensitive {
+                component.to_lowercase()
+            } else {
+                component.to_owned()
+            }
+        };
+        let path = if self.windows {
+            path.replace('\\', "/")
+        } else {
+            path.to_owned()
+        };
+        let mut rest = &path[..];
+        let mut components = Vec::new();
+        if self.windows {
+            if let Some(unc) = rest.strip_prefix("//") {
+                let mut parts = unc.splitn(3, '/');
+                let server = parts.next().unwrap_or("");
+                let share = parts.next().unwrap_or("");
+                components.push(case(&format!("//{}/{}", server, share)));
+                rest = parts.next().unwrap_or("");
+            } else if rest.len() >= 2
+                && rest.as_bytes()[1] == b':'
+                && rest.as_bytes()[0].is_ascii_alphabetic()
+            {
+                components.push(rest[..2].to_uppercase());
+                rest = &rest[2..];
+            }
+        }
+        let root = components.len();
+        for component in rest.split('/').filter(|component| !component.is_empty()) {
+            match component {
+                "." if self.resolve_dots => {}
+                ".." if self.resolve_dots => {
+                    if components.len() > root {
+                        components.pop();
+                    }
+                }
+                _ => components.push(case(component)),
+            }
+        }
+        components
+    }
+}
+
+/// A prefix tree keyed by filesystem paths, compared component by component according to the
+/// `PathOptions`, e.g. a mount table
+#[derive(Debug)]
+pub struct PathPrefixTree<V> {
+    tree: PrefixTree<String, V>,
+    options: PathOptions,
+}
+
+impl<V> PathPrefixTree<V> {
+    pub fn new(options: PathOptions) -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            options,
+        }
+    }
+
+    pub fn insert(&mut self, path: &str, value: V) -> Option<V> {
+        self.tree.insert(self.options.components(path), value)
+    }
+
+    pub fn get_exact_match(&self, path: &str) -> Option<&V> {
+        self.tree.get_exact_match(self.options.components(path))
+    }
+
+    pub fn get_by_shortest_prefix(&self, path: &str) -> Option<&V> {
+        self.tree
+            .get_by_shortest_prefix(self.options.components(path))
+    }
+
+    /// Returns the value at the longest prefix of the path (or `None` if no prefixes were found),
+    /// i.e. the innermost mount point containing the path
+    pub fn get_mount(&self, path: &str) -> Option<&V> {
+        self.tree
+            .get_by_longest_prefix(self.options.components(path))
+    }
+
+    pub fn remove_exact_match(&mut self, path: &str) -> Option<V> {
+        self.tree.remove_exact_match(self.options.components(path))
+    }
+
+    pub fn options(&self) -> PathOptions {
+        self.options
+    }
+
+    pub fn tree(&self) -> &PrefixTree<String, V> {
+        &self.tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_prefix_tree() {
+        let windows = PathOptions::windows();
+        assert_eq!(
+            windows.components(r"c:\Users\.\Me\..\Public"),
+            vec!["C:", "users", "public"]
+        );
+        assert_eq!(
+            windows.components(r"\\Server\Share\..\..\x"),
+            vec!["//server/share", "x"]
+        );
+        assert_eq!(
+            PathOptions::default().components("/usr//bin/../"),
+            vec!["usr", "bin", ".."]
+        );
+
+        let mut mounts = PathPrefixTree::new(windows);
+        mounts.insert(r"C:\", "system");
+        mounts.insert("c:/Data", "data");
+        mounts.insert(r"\\nas\media", "nas");
+        assert_eq!(mounts.get_mount(r"C:\DATA\photos"), Some(&"data"));
+        assert_eq!(mounts.get_mount(r"C:\Windows"), Some(&"system"));
+        assert_eq!(mounts.get_mount("//NAS/Media/films"), Some(&"nas"));
+        assert_eq!(mounts.get_mount(r"D:\"), None);
+        assert_eq!(mounts.get_by_shortest_prefix(r"C:\data"), Some(&"system"));
+    }
+}
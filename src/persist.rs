@@ -0,0 +1,496 @@
+use std::borrow::Borrow;
+use std::fmt::{Display, Write as _};
+use std::fs;
+use std::hash::{BuildHasher, Hash};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::PrefixTree;
+
+// The entries are stored as text, one entry per line: the number of key items, then every key item
+// and finally the value, each written as `<length in bytes>:<text>`, so the texts themselves may
+// contain any characters (including spaces and line breaks):
+//
+//     2 1:a1:b 5:hello
+
+impl<K: Hash + Eq + Display, V: Display, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Writes all the entries of the tree to the writer in the textual format that
+    /// `read_entries` accepts, in the order of the children maps; trees hashed with the same
+    /// `SeededState` and built by the same operations give byte-identical output (see
+    /// `write_entries_sorted` for output that doesn't depend on the hashing at all)
+    pub fn write_entries(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut entries = String::new();
+        encode_entries(self, &mut Vec::new(), &mut entries);
+        writer.write_all(entries.as_bytes())
+    }
+}
+
+impl<K: Hash + Eq + Ord + Display, V: Display, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Same as `write_entries`, but in lexicographic order of the keys, so the output only
+    /// depends on the entries of the tree and not on the hashing of the keys
+    pub fn write_entries_sorted(&self, writer: &mut impl Write) -> io::Result<()> {
+        let mut entries = String::new();
+        encode_entries_sorted(self, &mut Vec::new(), &mut entries);
+        writer.write_all(entries.as_bytes())
+    }
+
+    /// Writes the tree into the directory as a set of shard files: one file for the entries of
+    /// every subtree at depth `shard_depth` and one file for all the entries above that depth
+    /// (with `shard_depth` 0, the only shard holds the whole tree). The shards of an earlier save
+    /// into the directory are removed first. Each shard can later be loaded on its own with
+    /// `load_shard`. The entries are written in the order of `write_entries_sorted`, so saving
+    /// the same entries always gives the same files
+    pub fn save_sharded(&self, dir: impl AsRef<Path>, shard_depth: usize) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        remove_shards(dir)?;
+        let mut top = String::new();
+        save_shards(self, dir, shard_depth, &mut Vec::new(), &mut top)?;
+        if shard_depth == 0 {
+            // The shard of the empty prefix is the top file itself
+            return Ok(());
+        }
+        fs::write(dir.join(shard_file_name(&[] as &[&K])), top)
+    }
+}
+
+impl<K: Hash + Eq + FromStr, V: FromStr, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Inserts all the entries written by `write_entries` into the tree
+    pub fn read_entries(&mut self, reader: &mut impl Read) -> io::Result<()> {
+        self.read_entries_limited(reader, ReadLimits::default())
+    }
+
+    /// Same as `read_entries`, but fails with `InvalidData` as soon as the input exceeds the
+    /// limits, which makes it suitable for untrusted input. No more than `max_bytes` bytes of the
+    /// input are ever buffered. The entries read before the failure stay in the tree
+    pub fn read_entries_limited(
+        &mut self,
+        reader: &mut impl Read,
+        limits: ReadLimits,
+    ) -> io::Result<()> {
+        let mut text = String::new();
+        match limits.max_bytes {
+            Some(max_bytes) => {
+                // One byte past the limit tells a longer input from one of exactly the limit
+                reader
+                    .take(max_bytes as u64 + 1)
+                    .read_to_string(&mut text)?;
+                if text.len() > max_bytes {
+                    return Err(invalid_data("input too long"));
+                }
+            }
+            None => {
+                reader.read_to_string(&mut text)?;
+            }
+        }
+        let mut parser = Parser { rest: &text };
+        let mut nodes = 0;
+        while !parser.rest.is_empty() {
+            let (key, value): (Vec<K>, V) = parser.entry(limits.max_depth)?;
+            nodes += missing_nodes(self, &key);
+            if limits
+                .max_nodes
+                .map_or(false, |max_nodes| nodes > max_nodes)
+            {
+                return Err(invalid_data("too many nodes"));
+            }
+            self.insert(key, value);
+        }
+        Ok(())
+    }
+
+    /// Inserts the entries of the shard with the given prefix from a directory written by
+    /// `save_sharded` into the tree. The prefix must be exactly `shard_depth` items long, or empty
+    /// to load the entries above the shard depth
+    pub fn load_shard<I: Borrow<K>>(
+        &mut self,
+        dir: impl AsRef<Path>,
+        prefix: impl IntoIterator<Item = I>,
+    ) -> io::Result<()>
+    where
+        K: Display,
+    {
+        let prefix: Vec<I> = prefix.into_iter().collect();
+        let prefix: Vec<&K> = prefix.iter().map(Borrow::borrow).collect();
+        let mut file = fs::File::open(dir.as_ref().join(shard_file_name(&prefix)))?;
+        self.read_entries(&mut file)
+    }
+}
+
+/// Limits on the input of `PrefixTree::read_entries_limited`; `None` means unlimited
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReadLimits {
+    /// The maximum number of items in a key
+    pub max_depth: Option<usize>,
+    /// The maximum number of nodes that reading may add to the tree
+    pub max_nodes: Option<usize>,
+    /// The maximum size of the input in bytes
+    pub max_bytes: Option<usize>,
+}
+
+/// Returns how many nodes inserting the key would create
+fn missing_nodes<K: Hash + Eq, V, S: BuildHasher + Clone>(
+    mut root: &PrefixTree<K, V, S>,
+    key: &[K],
+) -> usize {
+    for (depth, item) in key.iter().enumerate() {
+        root = match root.subtrees.get(item) {
+            Some(subtree) => subtree,
+            None => return key.len() - depth,
+        };
+    }
+    0
+}
+
+fn encode_field(text: impl Display, output: &mut String) {
+    let text = text.to_string();
+    write!(output, "{}:{}", text.len(), text).unwrap();
+}
+
+fn encode_entry<K: Display, V: Display>(key: &[&K], value: &V, output: &mut String) {
+    write!(output, "{} ", key.len()).unwrap();
+    for item in key {
+        encode_field(item, output);
+    }
+    output.push(' ');
+    encode_field(value, output);
+    output.push('\n');
+}
+
+fn encode_entries<'a, K: Hash + Eq + Display, V: Display, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    path: &mut Vec<&'a K>,
+    output: &mut String,
+) {
+    if let Some(value) = &root.value {
+        encode_entry(path, value, output);
+    }
+    for (key, subtree) in &root.subtrees {
+        path.push(key);
+        encode_entries(subtree, path, output);
+        path.pop();
+    }
+}
+
+fn encode_entries_sorted<'a, K: Hash + Eq + Ord + Display, V: Display, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    path: &mut Vec<&'a K>,
+    output: &mut String,
+) {
+    if let Some(value) = &root.value {
+        encode_entry(path, value, output);
+    }
+    for (key, subtree) in root.sorted_subtrees() {
+        path.push(key);
+        encode_entries_sorted(subtree, path, output);
+        path.pop();
+    }
+}
+
+fn save_shards<'a, K: Hash + Eq + Ord + Display, V: Display, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    dir: &Path,
+    shard_depth: usize,
+    path: &mut Vec<&'a K>,
+    top: &mut String,
+) -> io::Result<()> {
+    if path.len() == shard_depth {
+        let mut shard = String::new();
+        encode_entries_sorted(root, &mut path.clone(), &mut shard);
+        let file = dir.join(shard_file_name(path));
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        return fs::write(file, shard);
+    }
+    if let Some(value) = &root.value {
+        encode_entry(path, value, top);
+    }
+    for (key, subtree) in root.sorted_subtrees() {
+        path.push(key);
+        save_shards(subtree, dir, shard_depth, path, top)?;
+        path.pop();
+    }
+    Ok(())
+}
+
+/// The longest name of a shard file or directory, well below the limits of common filesystems
+const MAX_NAME_LEN: usize = 200;
+
+/// The path of a shard is its encoded prefix in hexadecimal, which is safe on any filesystem. A
+/// long name is split into directories of `MAX_NAME_LEN` characters, the last part being the
+/// file; the encoding of the prefix is self-delimiting, so different prefixes never share a path
+fn shard_file_name<K: Display>(prefix: &[&K]) -> PathBuf {
+    if prefix.is_empty() {
+        return PathBuf::from("top.shard");
+    }
+    let mut encoded = String::new();
+    for item in prefix {
+        encode_field(item, &mut encoded);
+    }
+    let mut name = String::new();
+    for byte in encoded.bytes() {
+        write!(name, "{:02x}", byte).unwrap();
+    }
+    let mut path = PathBuf::new();
+    while name.len() > MAX_NAME_LEN {
+        let rest = name.split_off(MAX_NAME_LEN);
+        path.push(name);
+        name = rest;
+    }
+    name.push_str(".shard");
+    path.push(name);
+    path
+}
+
+/// Removes the shard files and the directories of long shard names from the directory
+fn remove_shards(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+        if path.is_dir() {
+            if name.len() == MAX_NAME_LEN && name.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                fs::remove_dir_all(&path)?;
+            }
+        } else if name.ends_with(".shard") {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn expect(&mut self, expected: char) -> io::Result<()> {
+        match self.rest.strip_prefix(expected) {
+            Some(rest) => {
+                self.rest = rest;
+                Ok(())
+            }
+            None => Err(invalid_data("malformed entry")),
+        }
+    }
+
+    fn number(&mut self, terminator: char) -> io::Result<usize> {
+        let end = self
+            .rest
+            .find(terminator)
+            .ok_or_else(|| invalid_data("malformed entry"))?;
+        let number = self.rest[..end]
+            .parse()
+            .map_err(|_| invalid_data("malformed length"))?;
+        self.rest = &self.rest[end + 1..];
+        Ok(number)
+    }
+
+    fn field<T: FromStr>(&mut self) -> io::Result<T> {
+        let len = self.number(':')?;
+        let text = self
+            .rest
+            .get(..len)
+            .ok_or_else(|| invalid_data("truncated field"))?;
+        self.rest = &self.rest[len..];
+        text.parse().map_err(|_| invalid_data("unparsable field"))
+    }
+
+    fn entry<K: FromStr, V: FromStr>(
+        &mut self,
+        max_depth: Option<usize>,
+    ) -> io::Result<(Vec<K>, V)> {
+        let items = self.number(' ')?;
+        if max_depth.map_or(false, |max_depth| items > max_depth) {
+            return Err(invalid_data("key too long"));
+        }
+        let key = (0..items)
+            .map(|_| self.field())
+            .collect::<io::Result<_>>()?;
+        self.expect(' ')?;
+        let value = self.field()?;
+        self.expect('\n')?;
+        Ok((key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SeededState;
+
+    use super::*;
+
+    #[test]
+    fn test_entries_round_trip() {
+        let mut tree: PrefixTree<String, String> = PrefixTree::new();
+        tree.insert(["a b", "c"], "line\nbreak".to_owned());
+        tree.insert([""], "".to_owned());
+        tree.insert([] as [&str; 0], "root".to_owned());
+
+        let mut bytes = Vec::new();
+        tree.write_entries(&mut bytes).unwrap();
+        let mut read = PrefixTree::new();
+        read.read_entries(&mut &bytes[..]).unwrap();
+        assert_eq!(read, tree);
+
+        let mut broken = PrefixTree::<String, String>::new();
+        assert!(broken.read_entries(&mut &b"1 5:a"[..]).is_err());
+    }
+
+    #[test]
+    fn test_seeded_entries() {
+        let write = || {
+            let mut tree = PrefixTree::with_hasher(SeededState::new(3));
+            for key in ["ab", "ac", "b", "ca", "cb", "d", "e"] {
+                tree.insert(key.chars(), key.len());
+            }
+            let mut bytes = Vec::new();
+            tree.write_entries(&mut bytes).unwrap();
+            bytes
+        };
+        assert_eq!(write(), write());
+
+        let write_sorted = |keys: &[&str]| {
+            let mut tree = PrefixTree::new();
+            for key in keys {
+                tree.insert(key.chars(), key.len());
+            }
+            let mut bytes = Vec::new();
+            tree.write_entries_sorted(&mut bytes).unwrap();
+            String::from_utf8(bytes).unwrap()
+        };
+        let sorted = write_sorted(&["b", "ab", "a", "ca"]);
+        assert_eq!(sorted, "1 1:a 1:1\n2 1:a1:b 1:2\n1 1:b 1:1\n2 1:c1:a 1:2\n");
+        assert_eq!(write_sorted(&["ca", "a", "ab", "b"]), sorted);
+    }
+
+    #[test]
+    fn test_read_limits() {
+        let mut bytes = Vec::new();
+        let mut tree = PrefixTree::new();
+        tree.insert("abc".chars(), 1);
+        tree.insert("abd".chars(), 2);
+        tree.write_entries(&mut bytes).unwrap();
+
+        let read = |max_depth, max_nodes| {
+            let limits = ReadLimits {
+                max_depth,
+                max_nodes,
+                ..ReadLimits::default()
+            };
+            PrefixTree::<char, i32>::new().read_entries_limited(&mut &bytes[..], limits)
+        };
+        assert!(read(Some(3), Some(4)).is_ok());
+        assert!(read(Some(2), None).is_err());
+        assert!(read(None, Some(3)).is_err());
+
+        // The declared length is checked before anything is allocated for the key
+        let mut hostile = &b"99999999999 1:a 1:1\n"[..];
+        let limits = ReadLimits {
+            max_depth: Some(16),
+            ..ReadLimits::default()
+        };
+        let error = PrefixTree::<char, i32>::new()
+            .read_entries_limited(&mut hostile, limits)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let read = |max_bytes| {
+            let limits = ReadLimits {
+                max_bytes: Some(max_bytes),
+                ..ReadLimits::default()
+            };
+            PrefixTree::<char, i32>::new().read_entries_limited(&mut &bytes[..], limits)
+        };
+        assert!(read(bytes.len()).is_ok());
+        assert!(read(bytes.len() - 1).is_err());
+        // An endless input is cut off at the limit
+        let error = PrefixTree::<char, i32>::new()
+            .read_entries_limited(
+                &mut io::repeat(b'1'),
+                ReadLimits {
+                    max_bytes: Some(1024),
+                    ..ReadLimits::default()
+                },
+            )
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_shards() {
+        let dir = std::env::temp_dir().join(format!("preftree-shards-{}", std::process::id()));
+        let mut tree = PrefixTree::new();
+        tree.insert("a".chars(), 1);
+        tree.insert("ab".chars(), 2);
+        tree.insert("abc".chars(), 3);
+        tree.insert("ba".chars(), 4);
+        tree.save_sharded(&dir, 2).unwrap();
+
+        let mut loaded = PrefixTree::new();
+        loaded.load_shard(&dir, "ab".chars()).unwrap();
+        let mut expected = PrefixTree::new();
+        expected.insert("ab".chars(), 2);
+        expected.insert("abc".chars(), 3);
+        assert_eq!(loaded, expected);
+
+        loaded.load_shard(&dir, "".chars()).unwrap();
+        expected.insert("a".chars(), 1);
+        assert_eq!(loaded, expected);
+
+        assert!(loaded.load_shard(&dir, "xy".chars()).is_err());
+
+        // Saving again replaces the earlier shards
+        let mut tree = PrefixTree::new();
+        tree.insert("ba".chars(), 5);
+        tree.save_sharded(&dir, 2).unwrap();
+        assert!(PrefixTree::<char, i32>::new()
+            .load_shard(&dir, "ab".chars())
+            .is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_shards_of_depth_zero() {
+        let dir = std::env::temp_dir().join(format!("preftree-shards-0-{}", std::process::id()));
+        let mut tree = PrefixTree::new();
+        tree.insert("".chars(), 1);
+        tree.insert("ab".chars(), 2);
+        tree.save_sharded(&dir, 0).unwrap();
+
+        let mut loaded = PrefixTree::new();
+        loaded.load_shard(&dir, "".chars()).unwrap();
+        assert_eq!(loaded, tree);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_long_shard_names() {
+        let dir = std::env::temp_dir().join(format!("preftree-shards-long-{}", std::process::id()));
+        let long = "x".repeat(300);
+        let mut tree: PrefixTree<String, i32> = PrefixTree::new();
+        tree.insert([long.as_str(), "a"], 1);
+        tree.insert(["y", "b"], 2);
+        tree.save_sharded(&dir, 1).unwrap();
+
+        let mut loaded: PrefixTree<String, i32> = PrefixTree::new();
+        loaded.load_shard(&dir, [long.clone()]).unwrap();
+        let mut expected: PrefixTree<String, i32> = PrefixTree::new();
+        expected.insert([long.as_str(), "a"], 1);
+        assert_eq!(loaded, expected);
+
+        tree.save_sharded(&dir, 2).unwrap();
+        assert!(PrefixTree::<String, i32>::new()
+            .load_shard(&dir, [long])
+            .is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
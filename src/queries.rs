@@ -0,0 +1,122 @@
+use std::hash::Hash;
+
+use crate::{IntoKeyItem, PrefixTree};
+
+/// A fixed batch of queries stored in a tree of their own, created by `compile_queries`, so all
+/// of them are answered in one walk over a tree, which visits every common prefix of the
+/// queries once
+#[derive(Debug)]
+pub struct CompiledQueries<K: Hash + Eq> {
+    /// The indices of the queries that end at every node
+    queries: PrefixTree<K, Vec<usize>>,
+    count: usize,
+}
+
+/// The membership of one query in a tree, as `contains_exact`, `contains_prefix` and `is_prefix`
+/// would report it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueryAnswer {
+    pub contains_exact: bool,
+    pub contains_prefix: bool,
+    pub is_prefix: bool,
+}
+
+/// Prepares the queries to be answered together by `CompiledQueries::run`
+pub fn compile_queries<K: Hash + Eq, I: IntoKeyItem<K>, Q: IntoIterator<Item = I>>(
+    queries: impl IntoIterator<Item = Q>,
+) -> CompiledQueries<K> {
+    let mut compiled = CompiledQueries {
+        queries: PrefixTree::new(),
+        count: 0,
+    };
+    for query in queries {
+        compiled
+            .queries
+            .probe(query, Vec::new)
+            .1
+            .push(compiled.count);
+        compiled.count += 1;
+    }
+    compiled
+}
+
+impl<K: Hash + Eq> CompiledQueries<K> {
+    /// Answers every query about the tree; the answers are in the order of the queries
+    pub fn run<V>(&self, tree: &PrefixTree<K, V>) -> Vec<QueryAnswer> {
+        let mut answers = vec![QueryAnswer::default(); self.count];
+        answer(&self.queries, tree, false, &mut answers);
+        answers
+    }
+
+    /// Returns the number of queries
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+}
+
+/// Walks the queries and the tree together; `prefix_found` tells whether the tree has a value at
+/// some prefix of the current node
+fn answer<K: Hash + Eq, V>(
+    queries: &PrefixTree<K, Vec<usize>>,
+    tree: &PrefixTree<K, V>,
+    prefix_found: bool,
+    answers: &mut [QueryAnswer],
+) {
+    let prefix_found = prefix_found || tree.value.is_some();
+    for &index in queries.value.iter().flatten() {
+        answers[index] = QueryAnswer {
+            contains_exact: tree.value.is_some(),
+            contains_prefix: prefix_found,
+            is_prefix: !tree.is_empty(),
+        };
+    }
+    for (key, queries) in &queries.subtrees {
+        match tree.subtrees.get(key) {
+            Some(tree) => answer(queries, tree, prefix_found, answers),
+            // Only a value above can answer the queries that leave the tree
+            None if prefix_found => mark_prefix_found(queries, answers),
+            None => {}
+        }
+    }
+}
+
+fn mark_prefix_found<K: Hash + Eq>(
+    queries: &PrefixTree<K, Vec<usize>>,
+    answers: &mut [QueryAnswer],
+) {
+    for &index in queries.value.iter().flatten() {
+        answers[index].contains_prefix = true;
+    }
+    for queries in queries.subtrees.values() {
+        mark_prefix_found(queries, answers);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_queries() {
+        let mut tree = PrefixTree::new();
+        tree.insert("ab".chars(), ());
+        tree.insert("abcd".chars(), ());
+
+        let queries = ["ab", "abc", "a", "abx", "x", "ab"];
+        let compiled = compile_queries(queries.iter().map(|query| query.chars()));
+        let answers = compiled.run(&tree);
+        for (query, answer) in queries.iter().zip(&answers) {
+            let expected = QueryAnswer {
+                contains_exact: tree.contains_exact(query.chars()),
+                contains_prefix: tree.contains_prefix(query.chars()),
+                is_prefix: tree.is_prefix(query.chars()),
+            };
+            assert_eq!(*answer, expected, "{}", query);
+        }
+        assert_eq!(compiled.len(), 6);
+    }
+}
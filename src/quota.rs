@@ -0,0 +1,133 @@
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+
+use crate::CountedPrefixTree;
+
+/// A limit on the number of keys under every prefix of `depth` items (the prefix itself
+/// included), e.g. the keys of every tenant when the keys start with the tenant name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaRule {
+    pub depth: usize,
+    pub max_entries: usize,
+}
+
+/// An insertion rejected by a `QuotaTree`, since the prefix already has `max_entries` keys under
+/// it; the value is given back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaExceeded<K, V> {
+    pub prefix: Vec<K>,
+    pub max_entries: usize,
+    pub value: V,
+}
+
+impl<K, V> Display for QuotaExceeded<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the quota of {} keys under a prefix of {} items is used up",
+            self.max_entries,
+            self.prefix.len()
+        )
+    }
+}
+
+impl<K: Debug, V: Debug> Error for QuotaExceeded<K, V> {}
+
+/// A prefix tree that enforces the quota rules on insertion. The numbers of keys are kept by a
+/// `CountedPrefixTree`, so checking a rule takes a walk down the prefix of the inserted key
+#[derive(Debug)]
+pub struct QuotaTree<K: Ord, V> {
+    tree: CountedPrefixTree<K, V>,
+    rules: Vec<QuotaRule>,
+}
+
+impl<K: Ord + Clone, V> QuotaTree<K, V> {
+    pub fn new(rules: impl IntoIterator<Item = QuotaRule>) -> Self {
+        Self {
+            tree: CountedPrefixTree::new(),
+            rules: rules.into_iter().collect(),
+        }
+    }
+
+    /// Same as `CountedPrefixTree::insert`, unless a new key would exceed a quota. Replacing the
+    /// value of an existing key is always allowed
+    pub fn insert(&mut self, key: &[K], value: V) -> Result<Option<V>, QuotaExceeded<K, V>> {
+        if self.tree.get_exact_match(key).is_none() {
+            for rule in &self.rules {
+                if rule.depth > key.len() {
+                    continue;
+                }
+                let prefix = &key[..rule.depth];
+                if self.tree.count_under_prefix(prefix) >= rule.max_entries {
+                    return Err(QuotaExceeded {
+                        prefix: prefix.to_vec(),
+                        max_entries: rule.max_entries,
+                        value,
+                    });
+                }
+            }
+        }
+        Ok(self.tree.insert(key, value))
+    }
+
+    pub fn get_exact_match(&self, key: &[K]) -> Option<&V> {
+        self.tree.get_exact_match(key)
+    }
+
+    pub fn get_by_shortest_prefix(&self, key: &[K]) -> Option<&V> {
+        self.tree.get_by_shortest_prefix(key)
+    }
+
+    pub fn remove_exact_match(&mut self, key: &[K]) -> Option<V> {
+        self.tree.remove_exact_match(key)
+    }
+
+    pub fn rules(&self) -> &[QuotaRule] {
+        &self.rules
+    }
+
+    pub fn tree(&self) -> &CountedPrefixTree<K, V> {
+        &self.tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: &str) -> Vec<&str> {
+        key.split('/').collect()
+    }
+
+    #[test]
+    fn test_quota_tree() {
+        let rules = [
+            QuotaRule {
+                depth: 1,
+                max_entries: 2,
+            },
+            QuotaRule {
+                depth: 0,
+                max_entries: 3,
+            },
+        ];
+        let mut tree = QuotaTree::new(rules);
+        assert_eq!(tree.insert(&key("a/x"), 1), Ok(None));
+        assert_eq!(tree.insert(&key("a/y"), 2), Ok(None));
+        assert_eq!(
+            tree.insert(&key("a/z"), 3),
+            Err(QuotaExceeded {
+                prefix: vec!["a"],
+                max_entries: 2,
+                value: 3
+            })
+        );
+        assert_eq!(tree.insert(&key("a/x"), 4), Ok(Some(1)));
+        assert_eq!(tree.insert(&key("b"), 5), Ok(None));
+        assert!(tree.insert(&key("c"), 6).unwrap_err().prefix.is_empty());
+
+        assert_eq!(tree.remove_exact_match(&key("a/y")), Some(2));
+        assert_eq!(tree.insert(&key("a/z"), 3), Ok(None));
+        assert_eq!(tree.tree().len(), 3);
+    }
+}
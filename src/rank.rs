@@ -0,0 +1,237 @@
+use std::borrow::Borrow;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+/// Scores entries for the queries that keep only the best-scored results (the higher score
+/// being better), such as `PrefixTree::top_completions_ranked`. The scorer is called during the
+/// walk, once for every entry the query finds. Any `FnMut(&[K], &V) -> S` closure is a ranker
+pub trait Ranker<K, V> {
+    type Score: Ord;
+
+    fn score(&mut self, key: &[K], value: &V) -> Self::Score;
+}
+
+impl<K, V, S: Ord, F: FnMut(&[K], &V) -> S> Ranker<K, V> for F {
+    type Score = S;
+
+    fn score(&mut self, key: &[K], value: &V) -> S {
+        self(key, value)
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns up to `limit` entries whose keys start with the prefix, with the largest values
+    /// first (and entries with equal values in lexicographic order of the keys)
+    pub fn top_completions<I: Borrow<K>>(
+        &self,
+        prefix: impl IntoIterator<Item = I>,
+        limit: usize,
+    ) -> Vec<(Vec<K>, &V)>
+    where
+        V: Ord,
+    {
+        self.top_completions_by(prefix, limit, |value| value)
+    }
+
+    /// Same as `top_completions`, but ranks the entries by the scores given by `score`. Only the
+    /// best `limit` entries found so far are kept during the walk, and a key is only built for an
+    /// entry that gets among them
+    pub fn top_completions_by<'a, I: Borrow<K>, T: Ord>(
+        &'a self,
+        prefix: impl IntoIterator<Item = I>,
+        limit: usize,
+        mut score: impl FnMut(&'a V) -> T,
+    ) -> Vec<(Vec<K>, &'a V)> {
+        let mut path = Vec::new();
+        let root = self.subtree(
+            prefix
+                .into_iter()
+                .inspect(|item| path.push(item.borrow().clone())),
+        );
+        let mut best = BestEntries::new(limit);
+        if let Some(root) = root {
+            collect_best(root, &mut path, &mut |key, value| {
+                best.offer(key, value, score(value))
+            });
+        }
+        best.into_sorted_vec()
+    }
+
+    /// Same as `top_completions_by`, but the ranker sees the keys too
+    pub fn top_completions_ranked<I: Borrow<K>, R: Ranker<K, V>>(
+        &self,
+        prefix: impl IntoIterator<Item = I>,
+        limit: usize,
+        mut ranker: R,
+    ) -> Vec<(Vec<K>, &V)> {
+        let mut path = Vec::new();
+        let root = self.subtree(
+            prefix
+                .into_iter()
+                .inspect(|item| path.push(item.borrow().clone())),
+        );
+        let mut best = BestEntries::new(limit);
+        if let Some(root) = root {
+            collect_best(root, &mut path, &mut |key, value| {
+                best.offer(key, value, ranker.score(key, value))
+            });
+        }
+        best.into_sorted_vec()
+    }
+}
+
+/// The best `limit` entries offered so far, kept in a min-heap whose top is the worst of them, so
+/// a key is only copied for an entry that gets among them
+pub(crate) struct BestEntries<'a, K, V, S> {
+    limit: usize,
+    heap: BinaryHeap<Reverse<Candidate<'a, K, V, S>>>,
+}
+
+impl<'a, K: Ord + Clone, V, S: Ord> BestEntries<'a, K, V, S> {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    pub(crate) fn offer(&mut self, key: &[K], value: &'a V, score: S) {
+        let better = self.heap.len() < self.limit
+            || self.heap.peek().map_or(false, |Reverse(worst)| {
+                (&score, Reverse(key)) > worst.rank()
+            });
+        if better {
+            if self.heap.len() == self.limit {
+                self.heap.pop();
+            }
+            self.heap.push(Reverse(Candidate {
+                score,
+                key: key.to_vec(),
+                value,
+            }));
+        }
+    }
+
+    /// Returns the entries, the best first
+    pub(crate) fn into_sorted_vec(self) -> Vec<(Vec<K>, &'a V)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(candidate)| (candidate.key, candidate.value))
+            .collect()
+    }
+
+    /// Same as `into_sorted_vec`, with the scores of the entries
+    pub(crate) fn into_sorted_vec_with_scores(self) -> Vec<(Vec<K>, S, &'a V)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(candidate)| (candidate.key, candidate.score, candidate.value))
+            .collect()
+    }
+}
+
+/// An entry ranked by its score, then by its key (the smaller key being better)
+struct Candidate<'a, K, V, S> {
+    score: S,
+    key: Vec<K>,
+    value: &'a V,
+}
+
+impl<K: Ord, V, S: Ord> Candidate<'_, K, V, S> {
+    fn rank(&self) -> (&S, Reverse<&[K]>) {
+        (&self.score, Reverse(&self.key))
+    }
+}
+
+impl<K: Ord, V, S: Ord> PartialEq for Candidate<'_, K, V, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rank() == other.rank()
+    }
+}
+
+impl<K: Ord, V, S: Ord> Eq for Candidate<'_, K, V, S> {}
+
+impl<K: Ord, V, S: Ord> PartialOrd for Candidate<'_, K, V, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V, S: Ord> Ord for Candidate<'_, K, V, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+/// Offers every entry of the subtree
+fn collect_best<'a, K: Hash + Eq + Clone, V, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    path: &mut Vec<K>,
+    offer: &mut impl FnMut(&[K], &'a V),
+) {
+    if let Some(value) = &root.value {
+        offer(path, value);
+    }
+    for (key, subtree) in &root.subtrees {
+        path.push(key.clone());
+        collect_best(subtree, path, offer);
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_completions() {
+        let mut tree = PrefixTree::new();
+        for (word, frequency) in [
+            ("car", 5),
+            ("cart", 9),
+            ("care", 5),
+            ("cat", 7),
+            ("dog", 10),
+        ] {
+            tree.insert(word.chars(), frequency);
+        }
+
+        let top: Vec<(String, i32)> = tree
+            .top_completions("ca".chars(), 3)
+            .into_iter()
+            .map(|(key, &value)| (key.into_iter().collect(), value))
+            .collect();
+        assert_eq!(
+            top,
+            [
+                ("cart".to_owned(), 9),
+                ("cat".to_owned(), 7),
+                ("car".to_owned(), 5)
+            ]
+        );
+        let lowest = tree.top_completions_by("".chars(), 1, |&value| Reverse(value));
+        assert_eq!(lowest, [(vec!['c', 'a', 'r'], &5)]);
+        assert!(tree.top_completions("ca".chars(), 0).is_empty());
+    }
+
+    #[test]
+    fn test_top_completions_ranked() {
+        let mut tree = PrefixTree::new();
+        for (word, frequency) in [("car", 5), ("cart", 9), ("care", 5), ("cat", 7)] {
+            tree.insert(word.chars(), frequency);
+        }
+
+        // The shorter keys first, the more frequent ones first among the keys of the same length
+        let ranker = |key: &[char], &frequency: &i32| (Reverse(key.len()), frequency);
+        let top = tree.top_completions_ranked("ca".chars(), 3, ranker);
+        let top: Vec<String> = top
+            .into_iter()
+            .map(|(key, _)| key.into_iter().collect())
+            .collect();
+        assert_eq!(top, ["cat", "car", "cart"]);
+    }
+}
@@ -0,0 +1,122 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+use crate::{IntoKeyItem, Op, PrefixTree};
+
+/// A destination for the operations logged by a `RecordingTree`
+pub trait OpSink<K, V> {
+    /// Takes the operation before it is applied
+    fn record(&mut self, op: &Op<K, V>);
+}
+
+impl<K: Clone, V: Clone> OpSink<K, V> for Vec<Op<K, V>> {
+    fn record(&mut self, op: &Op<K, V>) {
+        self.push(op.clone());
+    }
+}
+
+/// A prefix tree that logs all of its mutations to a sink, so a tree that went wrong can be
+/// reproduced elsewhere by passing the log to `PrefixTree::replay`
+#[derive(Debug)]
+pub struct RecordingTree<K: Hash + Eq, V, S> {
+    tree: PrefixTree<K, V>,
+    sink: S,
+}
+
+impl<K: Hash + Eq, V, S: OpSink<K, V>> RecordingTree<K, V, S> {
+    pub fn new(sink: S) -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            sink,
+        }
+    }
+
+    /// Same as `PrefixTree::insert`; the insertion is logged
+    pub fn insert<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        value: V,
+    ) -> Option<V> {
+        let key = sequence
+            .into_iter()
+            .map(IntoKeyItem::into_key_item)
+            .collect();
+        self.apply(Op::Insert(key, value))
+    }
+
+    /// Same as `PrefixTree::remove_exact_match`; the removal is logged
+    pub fn remove_exact_match<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<V> {
+        let key = sequence
+            .into_iter()
+            .map(IntoKeyItem::into_key_item)
+            .collect();
+        self.apply(Op::RemoveExactMatch(key))
+    }
+
+    /// Same as `PrefixTree::remove_by_shortest_prefix`; the removal is logged
+    pub fn remove_by_shortest_prefix<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<V> {
+        let key = sequence
+            .into_iter()
+            .map(IntoKeyItem::into_key_item)
+            .collect();
+        self.apply(Op::RemoveByShortestPrefix(key))
+    }
+
+    /// Logs the operation and applies it to the tree
+    pub fn apply(&mut self, op: Op<K, V>) -> Option<V> {
+        self.sink.record(&op);
+        self.tree.apply(op)
+    }
+
+    pub fn get_exact_match<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        self.tree.get_exact_match(sequence)
+    }
+
+    pub fn get_by_shortest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        self.tree.get_by_shortest_prefix(sequence)
+    }
+
+    pub fn tree(&self) -> &PrefixTree<K, V> {
+        &self.tree
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn into_parts(self) -> (PrefixTree<K, V>, S) {
+        (self.tree, self.sink)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_tree() {
+        let mut tree = RecordingTree::new(Vec::new());
+        tree.insert("ab".chars(), 1);
+        tree.insert("a".chars(), 2);
+        assert_eq!(tree.remove_by_shortest_prefix("abc".chars()), Some(2));
+        assert_eq!(tree.remove_exact_match("x".chars()), None);
+        assert_eq!(tree.get_by_shortest_prefix("abc".chars()), Some(&1));
+
+        assert_eq!(tree.sink().len(), 4);
+        assert_eq!(tree.sink()[3], Op::RemoveExactMatch(vec!['x']));
+        let (tree, log) = tree.into_parts();
+        assert_eq!(PrefixTree::replay(log), tree);
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+
+use crate::PrefixTree;
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Replaces every key item with `mapping(item)`, moving the subtrees instead of rebuilding
+    /// them. Children mapped to the same item are merged, and two values meeting at the same key
+    /// are combined by `resolver` (which gets them in no particular order)
+    pub fn relabel(
+        &mut self,
+        mut mapping: impl FnMut(&K) -> K,
+        mut resolver: impl FnMut(V, V) -> V,
+    ) {
+        relabel_node(self, &mut mapping, &mut resolver);
+    }
+}
+
+fn relabel_node<K: Hash + Eq, V, S: BuildHasher + Clone>(
+    root: &mut PrefixTree<K, V, S>,
+    mapping: &mut impl FnMut(&K) -> K,
+    resolver: &mut impl FnMut(V, V) -> V,
+) {
+    let hash_builder = root.subtrees.hasher().clone();
+    for (key, mut subtree) in mem::replace(&mut root.subtrees, HashMap::with_hasher(hash_builder)) {
+        relabel_node(&mut subtree, mapping, resolver);
+        match root.subtrees.entry(mapping(&key)) {
+            Entry::Occupied(entry) => merge_into(entry.into_mut(), subtree, resolver),
+            Entry::Vacant(entry) => {
+                entry.insert(subtree);
+            }
+        }
+    }
+}
+
+/// Moves the entries of `source` into `target`
+fn merge_into<K: Hash + Eq, V, S: BuildHasher + Clone>(
+    target: &mut PrefixTree<K, V, S>,
+    source: PrefixTree<K, V, S>,
+    resolver: &mut impl FnMut(V, V) -> V,
+) {
+    if let Some(value) = source.value {
+        target.value = Some(match target.value.take() {
+            Some(existing) => resolver(existing, value),
+            None => value,
+        });
+    }
+    for (key, subtree) in source.subtrees {
+        match target.subtrees.entry(key) {
+            Entry::Occupied(entry) => merge_into(entry.into_mut(), subtree, resolver),
+            Entry::Vacant(entry) => {
+                entry.insert(subtree);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relabel() {
+        let mut tree = PrefixTree::new();
+        tree.insert("Ab".chars(), 1);
+        tree.insert("ab".chars(), 2);
+        tree.insert("aBc".chars(), 4);
+        tree.insert("B".chars(), 8);
+
+        tree.relabel(char::to_ascii_lowercase, |a, b| a + b);
+        let mut expected = PrefixTree::new();
+        expected.insert("ab".chars(), 3);
+        expected.insert("abc".chars(), 4);
+        expected.insert("b".chars(), 8);
+        assert_eq!(tree, expected);
+    }
+}
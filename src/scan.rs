@@ -0,0 +1,141 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+/// The keys of a tree prepared for finding all their occurrences in a sequence in one pass (the
+/// Aho-Corasick algorithm), created by `PrefixTree::scanner`. It borrows the tree, so it can be
+/// reused for any number of sequences as long as the tree isn't changed
+#[derive(Debug)]
+pub struct Scanner<'a, K, V> {
+    /// The nodes of the tree in breadth-first order; node 0 is the root
+    nodes: Vec<ScanNode<'a, K, V>>,
+}
+
+#[derive(Debug)]
+struct ScanNode<'a, K, V> {
+    children: HashMap<&'a K, usize>,
+    /// The node of the longest proper suffix of this node's key that is in the tree
+    fail: usize,
+    /// The node of the longest proper suffix of this node's key that has a value
+    output: Option<usize>,
+    depth: usize,
+    value: Option<&'a V>,
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Prepares the keys for `Scanner::find_matches`; the preparation visits every node once
+    pub fn scanner(&self) -> Scanner<'_, K, V> {
+        let mut nodes = vec![ScanNode {
+            children: HashMap::new(),
+            fail: 0,
+            output: None,
+            depth: 0,
+            value: None,
+        }];
+        let mut queue = VecDeque::from([(0, self)]);
+        while let Some((index, tree)) = queue.pop_front() {
+            for (item, subtree) in &tree.subtrees {
+                let child = nodes.len();
+                let fail = if index == 0 {
+                    0
+                } else {
+                    next_state(&nodes, nodes[index].fail, item)
+                };
+                let output = if nodes[fail].value.is_some() {
+                    Some(fail)
+                } else {
+                    nodes[fail].output
+                };
+                nodes.push(ScanNode {
+                    children: HashMap::new(),
+                    fail,
+                    output,
+                    depth: nodes[index].depth + 1,
+                    value: subtree.value.as_ref(),
+                });
+                nodes[index].children.insert(item, child);
+                queue.push_back((child, subtree));
+            }
+        }
+        Scanner { nodes }
+    }
+
+    /// Same as `self.scanner().find_matches(haystack)`; build the scanner once instead when
+    /// scanning many sequences
+    pub fn find_matches(&self, haystack: &[K]) -> Vec<(usize, usize, &V)> {
+        self.scanner().find_matches(haystack)
+    }
+}
+
+/// Follows the failure links from the state until the item can be taken
+fn next_state<K: Hash + Eq, V>(nodes: &[ScanNode<'_, K, V>], mut state: usize, item: &K) -> usize {
+    loop {
+        if let Some(&child) = nodes[state].children.get(item) {
+            return child;
+        }
+        if state == 0 {
+            return 0;
+        }
+        state = nodes[state].fail;
+    }
+}
+
+impl<'a, K: Hash + Eq, V> Scanner<'a, K, V> {
+    /// Returns every occurrence of a nonempty key in the haystack as its start, its length and the
+    /// value of the key, in the order of the ends of the occurrences, the longer ones first for
+    /// the same end. Takes time proportional to the length of the haystack plus the number of
+    /// occurrences
+    pub fn find_matches(&self, haystack: &[K]) -> Vec<(usize, usize, &'a V)> {
+        let mut matches = Vec::new();
+        let mut state = 0;
+        for (position, item) in haystack.iter().enumerate() {
+            state = next_state(&self.nodes, state, item);
+            let mut found = if self.nodes[state].value.is_some() {
+                Some(state)
+            } else {
+                self.nodes[state].output
+            };
+            while let Some(node) = found {
+                let node = &self.nodes[node];
+                if let Some(value) = node.value {
+                    matches.push((position + 1 - node.depth, node.depth, value));
+                }
+                found = node.output;
+            }
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_matches() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["he", "she", "his", "hers", "e"].into_iter().enumerate() {
+            tree.insert(key.chars(), index);
+        }
+        tree.insert("".chars(), 5);
+
+        let haystack: Vec<char> = "ushers".chars().collect();
+        let mut expected = Vec::new();
+        for start in 0..haystack.len() {
+            for (len, &value) in tree.prefix_matches(&haystack[start..]) {
+                if len > 0 {
+                    expected.push((start, len, value));
+                }
+            }
+        }
+        let mut matches: Vec<_> = tree
+            .find_matches(&haystack)
+            .into_iter()
+            .map(|(start, len, &value)| (start, len, value))
+            .collect();
+        assert_eq!(matches[..3], [(1, 3, 1), (2, 2, 0), (3, 1, 4)]);
+        matches.sort_unstable();
+        assert_eq!(matches, expected);
+    }
+}
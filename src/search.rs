@@ -0,0 +1,759 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, Hash};
+use std::ops::Bound;
+
+use crate::rank::BestEntries;
+use crate::{PrefixTree, Ranker};
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns all the keys of exactly `len` items whose every item satisfies
+    /// `predicate(position, item)`, along with their values, in lexicographic order. The walk
+    /// never enters a child that fails the predicate, so e.g. a crossword pattern only visits the
+    /// branches that fit it
+    pub fn enumerate_matching(
+        &self,
+        len: usize,
+        mut predicate: impl FnMut(usize, &K) -> bool,
+    ) -> Vec<(Vec<K>, &V)> {
+        let mut keys = Vec::new();
+        enumerate_node(self, len, &mut predicate, &mut Vec::new(), &mut keys);
+        keys
+    }
+
+    /// Returns all the keys that can be assembled from the items of the bag (every item of the bag
+    /// used at most once, like letters of a Scrabble rack), along with their values, in
+    /// lexicographic order. Only the children whose items are still left in the bag are visited, and
+    /// every node goes through its children or the distinct items of the bag, whichever are fewer
+    pub fn find_with_letter_bag(&self, bag: impl IntoIterator<Item = K>) -> Vec<(Vec<K>, &V)> {
+        let mut counts = BTreeMap::new();
+        for item in bag {
+            *counts.entry(item).or_insert(0usize) += 1;
+        }
+        let mut keys = Vec::new();
+        bag_node(self, &mut counts, &mut Vec::new(), &mut keys);
+        keys.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        keys
+    }
+}
+
+/// An item of a pattern for `PrefixTree::get_with_wildcards`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryItem<K> {
+    /// Matches this key item only
+    Literal(K),
+    /// Matches any single key item
+    Any,
+    /// Matches any number of key items, none included (like `#` in MQTT topic filters)
+    AnySequence,
+}
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns all the keys that match the pattern item by item, along with their values, in
+    /// lexicographic order. The walk keeps the set of the pattern positions every node can be at
+    /// (more than one after an `AnySequence`), so every key is found once. Literal items take
+    /// single steps down the tree; only `Any` and `AnySequence` branch out
+    pub fn get_with_wildcards(&self, pattern: &[QueryItem<K>]) -> Vec<(Vec<K>, &V)> {
+        let mut keys = Vec::new();
+        let start = skip_sequences(pattern, vec![0]);
+        wildcard_node(self, pattern, &start, &mut Vec::new(), &mut |key, value| {
+            keys.push((key.to_vec(), value))
+        });
+        keys
+    }
+
+    /// Same as `get_with_wildcards`, but only returns the `limit` matches best scored by the
+    /// ranker, the best first
+    pub fn top_with_wildcards<R: Ranker<K, V>>(
+        &self,
+        pattern: &[QueryItem<K>],
+        limit: usize,
+        mut ranker: R,
+    ) -> Vec<(Vec<K>, &V)> {
+        let mut best = BestEntries::new(limit);
+        let start = skip_sequences(pattern, vec![0]);
+        wildcard_node(self, pattern, &start, &mut Vec::new(), &mut |key, value| {
+            best.offer(key, value, ranker.score(key, value))
+        });
+        best.into_sorted_vec()
+    }
+}
+
+/// A deterministic automaton over key items, for `PrefixTree::accepted_by`
+pub trait Automaton<K> {
+    type State;
+
+    /// Returns the state before any items
+    fn start(&self) -> Self::State;
+
+    /// Returns the state after the item, or `None` if no continuation can be accepted anymore
+    fn step(&self, state: &Self::State, item: &K) -> Option<Self::State>;
+
+    /// Returns `true` if the items that led to the state are accepted
+    fn is_match(&self, state: &Self::State) -> bool;
+}
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns all the keys accepted by the automaton, along with their values, in lexicographic
+    /// order. The tree and the automaton are walked together, and a subtree is skipped as soon as
+    /// the automaton has no state for its key, so fuzzy or pattern matching implemented as an
+    /// automaton only visits the branches that can still match
+    pub fn accepted_by<A: Automaton<K>>(&self, automaton: &A) -> Vec<(Vec<K>, &V)> {
+        let mut keys = Vec::new();
+        automaton_node(
+            self,
+            automaton,
+            &automaton.start(),
+            &mut Vec::new(),
+            &mut keys,
+        );
+        keys
+    }
+}
+
+fn automaton_node<'a, K: Hash + Eq + Ord + Clone, V, A: Automaton<K>, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    automaton: &A,
+    state: &A::State,
+    path: &mut Vec<K>,
+    keys: &mut Vec<(Vec<K>, &'a V)>,
+) {
+    if let Some(value) = &root.value {
+        if automaton.is_match(state) {
+            keys.push((path.clone(), value));
+        }
+    }
+    for (key, subtree) in root.sorted_subtrees() {
+        if let Some(state) = automaton.step(state, key) {
+            path.push(key.clone());
+            automaton_node(subtree, automaton, &state, path, keys);
+            path.pop();
+        }
+    }
+}
+
+/// Walks the subtree matching the pattern from the positions (sorted, without duplicates, and
+/// with every `AnySequence` also skipped)
+fn wildcard_node<'a, K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    pattern: &[QueryItem<K>],
+    positions: &[usize],
+    path: &mut Vec<K>,
+    emit: &mut impl FnMut(&[K], &'a V),
+) {
+    if let Some(value) = &root.value {
+        // The end of the pattern is the largest position possible
+        if positions.last() == Some(&pattern.len()) {
+            emit(path, value);
+        }
+    }
+    let mut visit = |key: &K, subtree| {
+        let next = skip_sequences(pattern, step_positions(pattern, positions, key));
+        if !next.is_empty() {
+            path.push(key.clone());
+            wildcard_node(subtree, pattern, &next, path, emit);
+            path.pop();
+        }
+    };
+    let branches = positions.iter().any(|&position| {
+        matches!(
+            pattern.get(position),
+            Some(QueryItem::Any | QueryItem::AnySequence)
+        )
+    });
+    if branches {
+        for (key, subtree) in root.sorted_subtrees() {
+            visit(key, subtree);
+        }
+        return;
+    }
+    let mut literals: Vec<&K> = positions
+        .iter()
+        .filter_map(|&position| match pattern.get(position) {
+            Some(QueryItem::Literal(key)) => Some(key),
+            _ => None,
+        })
+        .collect();
+    literals.sort_unstable();
+    literals.dedup();
+    for key in literals {
+        if let Some(subtree) = root.subtrees.get(key) {
+            visit(key, subtree);
+        }
+    }
+}
+
+/// Returns the positions of the pattern after the key item
+fn step_positions<K: Eq>(pattern: &[QueryItem<K>], positions: &[usize], key: &K) -> Vec<usize> {
+    positions
+        .iter()
+        .filter_map(|&position| match pattern.get(position)? {
+            QueryItem::Literal(literal) if literal == key => Some(position + 1),
+            QueryItem::Literal(_) => None,
+            QueryItem::Any => Some(position + 1),
+            QueryItem::AnySequence => Some(position),
+        })
+        .collect()
+}
+
+/// Adds the positions after the `AnySequence` items at the positions, which can match nothing
+fn skip_sequences<K>(pattern: &[QueryItem<K>], mut positions: Vec<usize>) -> Vec<usize> {
+    let mut index = 0;
+    while index < positions.len() {
+        let position = positions[index];
+        if let Some(QueryItem::AnySequence) = pattern.get(position) {
+            positions.push(position + 1);
+        }
+        index += 1;
+    }
+    positions.sort_unstable();
+    positions.dedup();
+    positions
+}
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns all the keys of the same length as the sequence where every item is either the
+    /// item of the sequence or one of its synonyms given by `synonyms` (e.g. `'0'` for `'o'`),
+    /// along with their values, in lexicographic order. Only the children matching an item or
+    /// its synonyms are visited
+    pub fn get_with_synonyms<Y: IntoIterator<Item = K>>(
+        &self,
+        sequence: &[K],
+        mut synonyms: impl FnMut(&K) -> Y,
+    ) -> Vec<(Vec<K>, &V)> {
+        let mut keys = Vec::new();
+        synonym_node(self, sequence, &mut synonyms, &mut Vec::new(), &mut keys);
+        keys
+    }
+}
+
+fn synonym_node<
+    'a,
+    K: Hash + Eq + Ord + Clone,
+    V,
+    Y: IntoIterator<Item = K>,
+    S: BuildHasher + Clone,
+>(
+    root: &'a PrefixTree<K, V, S>,
+    sequence: &[K],
+    synonyms: &mut impl FnMut(&K) -> Y,
+    path: &mut Vec<K>,
+    keys: &mut Vec<(Vec<K>, &'a V)>,
+) {
+    let (first, rest) = match sequence.split_first() {
+        Some(split) => split,
+        None => {
+            if let Some(value) = &root.value {
+                keys.push((path.clone(), value));
+            }
+            return;
+        }
+    };
+    let mut items: Vec<K> = synonyms(first).into_iter().collect();
+    items.push(first.clone());
+    items.sort_unstable();
+    items.dedup();
+    for item in items {
+        if let Some(subtree) = root.subtrees.get(&item) {
+            path.push(item);
+            synonym_node(subtree, rest, synonyms, path, keys);
+            path.pop();
+        }
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns all the keys within the Levenshtein distance `max_distance` of the sequence, with
+    /// their distances and values, in lexicographic order. Every node gets a row of the edit
+    /// distances between its key and the prefixes of the sequence, computed from the row of its
+    /// parent, and the walk stops at the nodes whose rows are all above `max_distance`
+    pub fn search_within_distance(
+        &self,
+        sequence: &[K],
+        max_distance: usize,
+    ) -> Vec<(Vec<K>, usize, &V)> {
+        let row: Vec<usize> = (0..=sequence.len()).collect();
+        let mut keys = Vec::new();
+        let search = DistanceSearch {
+            sequence,
+            max_distance,
+        };
+        search.visit(self, &row, &mut Vec::new(), &mut |key, distance, value| {
+            keys.push((key.to_vec(), distance, value))
+        });
+        keys
+    }
+
+    /// Same as `search_within_distance`, but only returns the `limit` nearest keys, the nearest
+    /// first, with the ties broken by the scores of the ranker (the best first) and then in
+    /// lexicographic order
+    pub fn top_within_distance<R: Ranker<K, V>>(
+        &self,
+        sequence: &[K],
+        max_distance: usize,
+        limit: usize,
+        mut ranker: R,
+    ) -> Vec<(Vec<K>, usize, &V)> {
+        let row: Vec<usize> = (0..=sequence.len()).collect();
+        let mut best = BestEntries::new(limit);
+        let search = DistanceSearch {
+            sequence,
+            max_distance,
+        };
+        search.visit(self, &row, &mut Vec::new(), &mut |key, distance, value| {
+            best.offer(key, value, (Reverse(distance), ranker.score(key, value)))
+        });
+        best.into_sorted_vec_with_scores()
+            .into_iter()
+            .map(|(key, (Reverse(distance), _), value)| (key, distance, value))
+            .collect()
+    }
+}
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns all the keys of the same length as the sequence that differ from it in at most
+    /// `max_mismatches` positions (the Hamming distance), with the numbers of mismatches and the
+    /// values, in lexicographic order. A branch is left as soon as it uses up the mismatches
+    pub fn get_with_mismatches(
+        &self,
+        sequence: &[K],
+        max_mismatches: usize,
+    ) -> Vec<(Vec<K>, usize, &V)> {
+        let mut keys = Vec::new();
+        mismatch_node(
+            self,
+            sequence,
+            max_mismatches,
+            0,
+            &mut Vec::new(),
+            &mut |key, mismatches, value| keys.push((key.to_vec(), mismatches, value)),
+        );
+        keys
+    }
+
+    /// Same as `get_with_mismatches`, but only returns the `limit` keys with the fewest
+    /// mismatches, the fewest first, with the ties broken by the scores of the ranker (the best
+    /// first) and then in lexicographic order
+    pub fn top_with_mismatches<R: Ranker<K, V>>(
+        &self,
+        sequence: &[K],
+        max_mismatches: usize,
+        limit: usize,
+        mut ranker: R,
+    ) -> Vec<(Vec<K>, usize, &V)> {
+        let mut best = BestEntries::new(limit);
+        mismatch_node(
+            self,
+            sequence,
+            max_mismatches,
+            0,
+            &mut Vec::new(),
+            &mut |key, mismatches, value| {
+                best.offer(key, value, (Reverse(mismatches), ranker.score(key, value)))
+            },
+        );
+        best.into_sorted_vec_with_scores()
+            .into_iter()
+            .map(|(key, (Reverse(mismatches), _), value)| (key, mismatches, value))
+            .collect()
+    }
+}
+
+fn mismatch_node<'a, K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    rest: &[K],
+    max_mismatches: usize,
+    mismatches: usize,
+    path: &mut Vec<K>,
+    emit: &mut impl FnMut(&[K], usize, &'a V),
+) {
+    let (first, rest) = match rest.split_first() {
+        Some(split) => split,
+        None => {
+            if let Some(value) = &root.value {
+                emit(path, mismatches, value);
+            }
+            return;
+        }
+    };
+    for (key, subtree) in root.sorted_subtrees() {
+        let mismatches = mismatches + (key != first) as usize;
+        if mismatches <= max_mismatches {
+            path.push(key.clone());
+            mismatch_node(subtree, rest, max_mismatches, mismatches, path, emit);
+            path.pop();
+        }
+    }
+}
+
+struct DistanceSearch<'s, K> {
+    sequence: &'s [K],
+    max_distance: usize,
+}
+
+impl<K: Hash + Eq + Ord + Clone> DistanceSearch<'_, K> {
+    /// `row[i]` is the distance between the key of the node and the first `i` items of the
+    /// sequence
+    fn visit<'a, V, S: BuildHasher + Clone>(
+        &self,
+        root: &'a PrefixTree<K, V, S>,
+        row: &[usize],
+        path: &mut Vec<K>,
+        emit: &mut impl FnMut(&[K], usize, &'a V),
+    ) {
+        let distance = row[self.sequence.len()];
+        if let (Some(value), true) = (&root.value, distance <= self.max_distance) {
+            emit(path, distance, value);
+        }
+        for (key, subtree) in root.sorted_subtrees() {
+            let mut next = Vec::with_capacity(row.len());
+            next.push(row[0] + 1);
+            for (i, item) in self.sequence.iter().enumerate() {
+                let substitution = row[i] + (item != key) as usize;
+                next.push(substitution.min(row[i + 1] + 1).min(next[i] + 1));
+            }
+            if next.iter().any(|&distance| distance <= self.max_distance) {
+                path.push(key.clone());
+                self.visit(subtree, &next, path, emit);
+                path.pop();
+            }
+        }
+    }
+}
+
+/// Visits the children that are left in the bag in no particular order, going through either the
+/// children or the bag, whichever is smaller
+fn bag_node<'a, K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    counts: &mut BTreeMap<K, usize>,
+    path: &mut Vec<K>,
+    keys: &mut Vec<(Vec<K>, &'a V)>,
+) {
+    if let Some(value) = &root.value {
+        keys.push((path.clone(), value));
+    }
+    if root.subtrees.len() <= counts.len() {
+        for (item, subtree) in &root.subtrees {
+            match counts.get_mut(item) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => continue,
+            }
+            path.push(item.clone());
+            bag_node(subtree, counts, path, keys);
+            path.pop();
+            *counts.get_mut(item).unwrap() += 1;
+        }
+        return;
+    }
+    let left = |(item, count): (&K, &usize)| if *count > 0 { Some(item.clone()) } else { None };
+    let mut next = counts.iter().find_map(left);
+    while let Some(item) = next {
+        if let Some(subtree) = root.subtrees.get(&item) {
+            *counts.get_mut(&item).unwrap() -= 1;
+            path.push(item.clone());
+            bag_node(subtree, counts, path, keys);
+            path.pop();
+            *counts.get_mut(&item).unwrap() += 1;
+        }
+        next = counts
+            .range((Bound::Excluded(&item), Bound::Unbounded))
+            .find_map(left);
+    }
+}
+
+fn enumerate_node<'a, K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone>(
+    root: &'a PrefixTree<K, V, S>,
+    len: usize,
+    predicate: &mut impl FnMut(usize, &K) -> bool,
+    path: &mut Vec<K>,
+    keys: &mut Vec<(Vec<K>, &'a V)>,
+) {
+    if path.len() == len {
+        if let Some(value) = &root.value {
+            keys.push((path.clone(), value));
+        }
+        return;
+    }
+    for (key, subtree) in root.sorted_subtrees() {
+        if predicate(path.len(), key) {
+            path.push(key.clone());
+            enumerate_node(subtree, len, predicate, path, keys);
+            path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enumerate_matching() {
+        let mut tree = PrefixTree::new();
+        for word in ["cat", "cot", "cut", "coat", "dot", "at"] {
+            tree.insert(word.chars(), ());
+        }
+
+        let pattern: Vec<char> = "c?t".chars().collect();
+        let keys: Vec<String> = tree
+            .enumerate_matching(3, |position, &item| {
+                pattern[position] == '?' || pattern[position] == item
+            })
+            .into_iter()
+            .map(|(key, _)| key.into_iter().collect())
+            .collect();
+        assert_eq!(keys, ["cat", "cot", "cut"]);
+        assert_eq!(tree.enumerate_matching(2, |_, _| true).len(), 1);
+        assert!(tree
+            .enumerate_matching(4, |_, &item| item != 'a')
+            .is_empty());
+    }
+
+    #[test]
+    fn test_find_with_letter_bag() {
+        let mut tree = PrefixTree::new();
+        for word in ["tea", "eat", "ate", "tee", "teat", "a"] {
+            tree.insert(word.chars(), ());
+        }
+
+        let keys: Vec<String> = tree
+            .find_with_letter_bag("teax".chars())
+            .into_iter()
+            .map(|(key, _)| key.into_iter().collect())
+            .collect();
+        assert_eq!(keys, ["a", "ate", "eat", "tea"]);
+        assert!(tree.find_with_letter_bag("".chars()).is_empty());
+    }
+
+    #[test]
+    fn test_search_within_distance() {
+        let mut tree = PrefixTree::new();
+        for word in ["hello", "help", "hell", "yellow", "world"] {
+            tree.insert(word.chars(), ());
+        }
+
+        let query: Vec<char> = "helo".chars().collect();
+        let found: Vec<(String, usize)> = tree
+            .search_within_distance(&query, 1)
+            .into_iter()
+            .map(|(key, distance, _)| (key.into_iter().collect(), distance))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("hell".to_owned(), 1),
+                ("hello".to_owned(), 1),
+                ("help".to_owned(), 1)
+            ]
+        );
+        assert_eq!(tree.search_within_distance(&query, 3).len(), 4);
+        assert!(tree.search_within_distance(&query, 0).is_empty());
+
+        // "yellow" is at distance 3, the others at 1; the longer keys win the ties
+        let longer = |key: &[char], _: &()| key.len();
+        let found: Vec<(String, usize)> = tree
+            .top_within_distance(&query, 3, 2, longer)
+            .into_iter()
+            .map(|(key, distance, _)| (key.into_iter().collect(), distance))
+            .collect();
+        assert_eq!(found, [("hello".to_owned(), 1), ("hell".to_owned(), 1)]);
+    }
+
+    #[test]
+    fn test_get_with_wildcards() {
+        let mut tree = PrefixTree::new();
+        for route in [
+            "GET /users/1/posts",
+            "GET /users/2/posts",
+            "GET /users/2/likes",
+        ] {
+            tree.insert(route.split_inclusive(&[' ', '/'][..]), route);
+        }
+
+        let literal = |segment: &str| QueryItem::Literal(segment.to_owned());
+        let pattern = [
+            literal("GET "),
+            literal("/"),
+            literal("users/"),
+            QueryItem::Any,
+            literal("posts"),
+        ];
+        let found: Vec<_> = tree
+            .get_with_wildcards(&pattern)
+            .into_iter()
+            .map(|(_, route)| *route)
+            .collect();
+        assert_eq!(found, ["GET /users/1/posts", "GET /users/2/posts"]);
+        assert!(tree.get_with_wildcards(&pattern[..4]).is_empty());
+
+        let pattern = [
+            literal("GET "),
+            literal("/"),
+            literal("users/"),
+            QueryItem::Any,
+            QueryItem::Any,
+        ];
+        // The largest user segment is the best, and the ties go in lexicographic order
+        let user = |key: &[String], _: &&str| key[3].clone();
+        let found: Vec<_> = tree
+            .top_with_wildcards(&pattern, 2, user)
+            .into_iter()
+            .map(|(_, route)| *route)
+            .collect();
+        assert_eq!(found, ["GET /users/2/likes", "GET /users/2/posts"]);
+    }
+
+    #[test]
+    fn test_any_sequence() {
+        let mut topics = PrefixTree::new();
+        for topic in [
+            "sensors",
+            "sensors/a/temp",
+            "sensors/b/temp",
+            "sensors/b/x/temp",
+            "other/temp",
+        ] {
+            topics.insert(topic.split('/'), topic);
+        }
+
+        let literal = |segment: &str| QueryItem::Literal(segment.to_owned());
+        let found = |pattern: &[QueryItem<String>]| -> Vec<&str> {
+            let found = topics.get_with_wildcards(pattern);
+            found.into_iter().map(|(_, topic)| *topic).collect()
+        };
+        assert_eq!(
+            found(&[literal("sensors"), QueryItem::Any, literal("temp")]),
+            ["sensors/a/temp", "sensors/b/temp"]
+        );
+        assert_eq!(
+            found(&[literal("sensors"), QueryItem::AnySequence]),
+            [
+                "sensors",
+                "sensors/a/temp",
+                "sensors/b/temp",
+                "sensors/b/x/temp"
+            ]
+        );
+        // Every key is found once even if the pattern can match it in several ways
+        let twice = [
+            QueryItem::AnySequence,
+            QueryItem::AnySequence,
+            literal("temp"),
+        ];
+        assert_eq!(
+            found(&twice),
+            [
+                "other/temp",
+                "sensors/a/temp",
+                "sensors/b/temp",
+                "sensors/b/x/temp"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_with_mismatches() {
+        let mut tree = PrefixTree::new();
+        for kmer in ["ACGT", "ACGA", "TCGA", "ACG", "AAAA"] {
+            tree.insert(kmer.chars(), ());
+        }
+
+        let query: Vec<char> = "ACGA".chars().collect();
+        let found: Vec<(String, usize)> = tree
+            .get_with_mismatches(&query, 1)
+            .into_iter()
+            .map(|(key, mismatches, _)| (key.into_iter().collect(), mismatches))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("ACGA".to_owned(), 0),
+                ("ACGT".to_owned(), 1),
+                ("TCGA".to_owned(), 1)
+            ]
+        );
+        assert_eq!(tree.get_with_mismatches(&query, 0).len(), 1);
+
+        let found: Vec<(String, usize)> = tree
+            .top_with_mismatches(&query, 1, 2, |_: &[char], _: &()| ())
+            .into_iter()
+            .map(|(key, mismatches, _)| (key.into_iter().collect(), mismatches))
+            .collect();
+        assert_eq!(found, [("ACGA".to_owned(), 0), ("ACGT".to_owned(), 1)]);
+    }
+
+    #[test]
+    fn test_get_with_synonyms() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["fool", "f0ol", "fo0l", "f00", "foo"]
+            .into_iter()
+            .enumerate()
+        {
+            tree.insert(key.chars(), index);
+        }
+
+        let confusables = |&item: &char| match item {
+            'o' => vec!['0', 'o'],
+            'l' => vec!['1'],
+            _ => vec![],
+        };
+        let keys: Vec<String> = tree
+            .get_with_synonyms(&['f', 'o', 'o', 'l'], confusables)
+            .into_iter()
+            .map(|(key, _)| key.into_iter().collect())
+            .collect();
+        assert_eq!(keys, ["f0ol", "fo0l", "fool"]);
+        // The synonyms only go one way: '0' doesn't stand for 'o'
+        let keys = tree.get_with_synonyms(&['f', '0', 'o'], confusables);
+        assert_eq!(keys, [(vec!['f', '0', '0'], &3)]);
+    }
+
+    /// Accepts the keys with a `b` somewhere after an `a`, rejecting any `c` before the `a`
+    struct AThenB;
+
+    impl Automaton<char> for AThenB {
+        /// How many of `a` and `b` were seen in order
+        type State = u8;
+
+        fn start(&self) -> u8 {
+            0
+        }
+
+        fn step(&self, &state: &u8, &item: &char) -> Option<u8> {
+            match (state, item) {
+                (0, 'c') => None,
+                (0, 'a') | (1, 'b') => Some(state + 1),
+                _ => Some(state),
+            }
+        }
+
+        fn is_match(&self, &state: &u8) -> bool {
+            state == 2
+        }
+    }
+
+    #[test]
+    fn test_accepted_by() {
+        let mut tree = PrefixTree::new();
+        for (index, key) in ["ab", "axb", "ba", "cab", "xab", "a"]
+            .into_iter()
+            .enumerate()
+        {
+            tree.insert(key.chars(), index);
+        }
+
+        let accepted: Vec<(String, usize)> = tree
+            .accepted_by(&AThenB)
+            .into_iter()
+            .map(|(key, &value)| (key.into_iter().collect(), value))
+            .collect();
+        assert_eq!(
+            accepted,
+            [
+                ("ab".to_owned(), 0),
+                ("axb".to_owned(), 1),
+                ("xab".to_owned(), 4)
+            ]
+        );
+    }
+}
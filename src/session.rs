@@ -0,0 +1,233 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::ops::{Deref, DerefMut};
+
+use crate::metrics::{counters, CountersRef};
+use crate::{descend_creating, IntoKeyItem, PrefixTree};
+
+/// A handle to the node at some prefix of a tree, created by `PrefixTree::session_at`, for
+/// inserting many keys under that prefix without walking down the prefix for every one of them.
+/// The prefix is walked by the first insertion, so a session that inserts nothing leaves the tree
+/// as it was
+pub struct InsertSession<'a, K: Hash + Eq, V, S = RandomState> {
+    /// The fields of the session node, or of the root until the prefix is walked; only `None`
+    /// while walking
+    #[allow(clippy::type_complexity)]
+    node: Option<(
+        &'a mut Option<V>,
+        &'a mut HashMap<K, PrefixTree<K, V, S>, S>,
+    )>,
+    /// The part of the prefix that is still to be walked
+    prefix: Vec<K>,
+    counters: CountersRef<'a>,
+}
+
+/// A key being inserted item by item, created by `PrefixTree::appender` or
+/// `InsertSession::appender`. Every pushed item takes one step down the tree, creating the node if
+/// needed, so the key is never buffered. The nodes are created as the items arrive: an appender
+/// dropped without `finish` leaves a branch without values behind (`canonicalize` removes it)
+pub struct Appender<'a, K: Hash + Eq, V, S = RandomState> {
+    /// The fields of the current node; only `None` while stepping
+    #[allow(clippy::type_complexity)]
+    node: Option<(
+        &'a mut Option<V>,
+        &'a mut HashMap<K, PrefixTree<K, V, S>, S>,
+    )>,
+    counters: CountersRef<'a>,
+}
+
+/// A mutable view of the entries under a prefix, created by `PrefixTree::subtree_mut`, which
+/// dereferences to a tree keyed relative to the prefix. The entries are moved out of the tree
+/// while the view exists and put back when it is dropped, removing the branch leading to the
+/// prefix if nothing is left under it
+pub struct SubtreeMut<'a, K: Hash + Eq + Clone, V, S: BuildHasher + Clone = RandomState> {
+    tree: &'a mut PrefixTree<K, V, S>,
+    prefix: Vec<K>,
+    subtree: PrefixTree<K, V, S>,
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns a mutable view of the entries at and under the prefix
+    pub fn subtree_mut<I: IntoKeyItem<K>>(
+        &mut self,
+        prefix: impl IntoIterator<Item = I>,
+    ) -> SubtreeMut<'_, K, V, S> {
+        let prefix: Vec<K> = prefix.into_iter().map(IntoKeyItem::into_key_item).collect();
+        let subtree = self.replace_prefix(
+            &prefix,
+            PrefixTree::with_hasher(self.subtrees.hasher().clone()),
+        );
+        SubtreeMut {
+            tree: self,
+            prefix,
+            subtree,
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher + Clone> Deref for SubtreeMut<'_, K, V, S> {
+    type Target = PrefixTree<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.subtree
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher + Clone> DerefMut for SubtreeMut<'_, K, V, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.subtree
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher + Clone> Drop for SubtreeMut<'_, K, V, S> {
+    fn drop(&mut self) {
+        let hash_builder = self.subtree.subtrees.hasher().clone();
+        let subtree = std::mem::replace(&mut self.subtree, PrefixTree::with_hasher(hash_builder));
+        self.tree.replace_prefix(&self.prefix, subtree);
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns an appender for a key inserted from the root
+    pub fn appender(&mut self) -> Appender<'_, K, V, S> {
+        Appender {
+            counters: counters!(self.metrics),
+            node: Some((&mut self.value, &mut self.subtrees)),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns a session for inserting keys under the prefix; the node at the prefix is created
+    /// by the first insertion if needed
+    pub fn session_at<I: IntoKeyItem<K>>(
+        &mut self,
+        prefix: impl IntoIterator<Item = I>,
+    ) -> InsertSession<'_, K, V, S> {
+        InsertSession {
+            counters: counters!(self.metrics),
+            node: Some((&mut self.value, &mut self.subtrees)),
+            prefix: prefix.into_iter().map(IntoKeyItem::into_key_item).collect(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> InsertSession<'_, K, V, S> {
+    /// Inserts the value at the session prefix followed by the suffix; returns the previous value
+    /// at the same key if there was one before
+    pub fn insert_suffix<I: IntoKeyItem<K>>(
+        &mut self,
+        suffix: impl IntoIterator<Item = I>,
+        value: V,
+    ) -> Option<V> {
+        let counters = self.counters;
+        let (node_value, subtrees) = self.node();
+        descend_creating(node_value, subtrees, counters, suffix)
+            .0
+            .replace(value)
+    }
+
+    /// Returns an appender for a key inserted under the session prefix
+    pub fn appender(&mut self) -> Appender<'_, K, V, S> {
+        let counters = self.counters;
+        Appender {
+            node: Some(self.node()),
+            counters,
+        }
+    }
+
+    /// Returns the fields of the session node, walking the prefix first if it's still pending
+    #[allow(clippy::type_complexity)]
+    fn node(&mut self) -> (&mut Option<V>, &mut HashMap<K, PrefixTree<K, V, S>, S>) {
+        if !self.prefix.is_empty() {
+            let (value, subtrees) = self.node.take().unwrap();
+            let prefix = std::mem::take(&mut self.prefix);
+            self.node = Some(descend_creating(value, subtrees, self.counters, prefix));
+        }
+        let (value, subtrees) = self.node.as_mut().unwrap();
+        (&mut **value, &mut **subtrees)
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> Appender<'_, K, V, S> {
+    /// Appends the item to the key
+    pub fn push(&mut self, item: impl IntoKeyItem<K>) {
+        let (value, subtrees) = self.node.take().unwrap();
+        self.node = Some(descend_creating(value, subtrees, self.counters, Some(item)));
+    }
+
+    /// Inserts the value at the key pushed so far; returns the previous value at the same key if
+    /// there was one before
+    pub fn finish(self, value: V) -> Option<V> {
+        self.node.unwrap().0.replace(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_session() {
+        let mut tree = PrefixTree::new();
+        let mut session = tree.session_at("api/".chars());
+        assert_eq!(session.insert_suffix("users".chars(), 1), None);
+        assert_eq!(session.insert_suffix("".chars(), 2), None);
+        assert_eq!(session.insert_suffix("users".chars(), 3), Some(1));
+
+        let mut expected = PrefixTree::new();
+        expected.insert("api/users".chars(), 3);
+        expected.insert("api/".chars(), 2);
+        assert_eq!(tree, expected);
+
+        // A session without insertions creates no nodes
+        tree.session_at("unused/".chars());
+        assert_eq!(tree, expected);
+        assert!(!tree.canonicalize());
+    }
+
+    #[test]
+    fn test_appender() {
+        let mut tree = PrefixTree::new();
+        tree.insert("ab".chars(), 1);
+        let mut appender = tree.appender();
+        for item in "abc".chars() {
+            appender.push(item);
+        }
+        assert_eq!(appender.finish(2), None);
+
+        let mut session = tree.session_at("a".chars());
+        let mut appender = session.appender();
+        appender.push('b');
+        assert_eq!(appender.finish(3), Some(1));
+
+        let mut expected = PrefixTree::new();
+        expected.insert("ab".chars(), 3);
+        expected.insert("abc".chars(), 2);
+        assert_eq!(tree, expected);
+    }
+
+    #[test]
+    fn test_subtree_mut() {
+        let mut tree = PrefixTree::new();
+        tree.insert("ns/a".chars(), 1);
+        tree.insert("ns/b".chars(), 2);
+        tree.insert("n".chars(), 3);
+
+        let mut namespace = tree.subtree_mut("ns/".chars());
+        assert_eq!(namespace.insert("c".chars(), 4), None);
+        assert_eq!(namespace.remove_exact_match("a".chars()), Some(1));
+        drop(namespace);
+        assert_eq!(tree.get_exact_match("ns/c".chars()), Some(&4));
+
+        let mut namespace = tree.subtree_mut("ns/".chars());
+        namespace.remove_exact_match("b".chars());
+        namespace.remove_exact_match("c".chars());
+        drop(namespace);
+        let mut expected = PrefixTree::new();
+        expected.insert("n".chars(), 3);
+        assert_eq!(tree, expected);
+        assert!(!tree.canonicalize());
+    }
+}
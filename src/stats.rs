@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+impl<K: Hash + Eq + Ord + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns up to `limit` prefixes of exactly `depth` items with the largest numbers of entries
+    /// stored at and under them, along with those numbers, the largest first (and prefixes with
+    /// equal numbers in lexicographic order). The counts are computed in a single walk over the
+    /// tree
+    pub fn heaviest_prefixes(&self, depth: usize, limit: usize) -> Vec<(Vec<K>, usize)> {
+        let mut prefixes = Vec::new();
+        count_at_depth(self, depth, &mut Vec::new(), &mut prefixes);
+        prefixes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        prefixes.truncate(limit);
+        prefixes
+    }
+}
+
+/// The shape of a tree, as reported by `PrefixTree::stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TreeStats {
+    pub nodes: usize,
+    pub entries: usize,
+    /// The length of the longest key
+    pub max_depth: usize,
+    /// The number of distinct key items
+    pub alphabet_size: usize,
+    /// The largest number of children of a node
+    pub max_fanout: usize,
+    /// The number of nodes with one child and no value, which path compression would merge into
+    /// their children
+    pub chain_nodes: usize,
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Walks the whole tree and returns its statistics, e.g. to decide whether a denser layout
+    /// (such as `to_transition_table`, which needs a small alphabet) would pay off
+    pub fn stats(&self) -> TreeStats {
+        let mut stats = TreeStats::default();
+        let mut alphabet = HashSet::new();
+        let mut stack = vec![(self, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            stats.nodes += 1;
+            if node.value.is_some() {
+                stats.entries += 1;
+                stats.max_depth = stats.max_depth.max(depth);
+            } else if node.subtrees.len() == 1 {
+                stats.chain_nodes += 1;
+            }
+            stats.max_fanout = stats.max_fanout.max(node.subtrees.len());
+            for (key, subtree) in &node.subtrees {
+                alphabet.insert(key);
+                stack.push((subtree, depth + 1));
+            }
+        }
+        stats.alphabet_size = alphabet.len();
+        stats
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Returns the longest sequence that all the keys of the tree start with (empty if the tree is
+    /// empty): the path down from the root through the nodes without values that have exactly one
+    /// child
+    pub fn longest_common_prefix(&self) -> Vec<K> {
+        let mut prefix = Vec::new();
+        let mut root = self;
+        while root.value.is_none() && root.subtrees.len() == 1 {
+            let (key, subtree) = root.subtrees.iter().next().unwrap();
+            prefix.push(key.clone());
+            root = subtree;
+        }
+        if root.is_empty() {
+            // A branch without values, left by editing the public fields by hand or by an
+            // unfinished `Appender`
+            prefix.clear();
+        }
+        prefix
+    }
+}
+
+fn count_at_depth<K: Hash + Eq + Clone, V, S: BuildHasher + Clone>(
+    root: &PrefixTree<K, V, S>,
+    depth: usize,
+    path: &mut Vec<K>,
+    prefixes: &mut Vec<(Vec<K>, usize)>,
+) {
+    if path.len() == depth {
+        let count = root.len();
+        if count > 0 {
+            prefixes.push((path.clone(), count));
+        }
+        return;
+    }
+    for (key, subtree) in &root.subtrees {
+        path.push(key.clone());
+        count_at_depth(subtree, depth, path, prefixes);
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heaviest_prefixes() {
+        let mut tree = PrefixTree::new();
+        for key in ["aa", "ab", "ac", "b", "ba", "bb", "c", "d"] {
+            tree.insert(key.chars(), ());
+        }
+
+        assert_eq!(
+            tree.heaviest_prefixes(1, 3),
+            vec![(vec!['a'], 3), (vec!['b'], 3), (vec!['c'], 1)]
+        );
+        assert_eq!(tree.heaviest_prefixes(0, 1), vec![(vec![], 8)]);
+        assert_eq!(tree.heaviest_prefixes(5, 1), vec![]);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut tree = PrefixTree::new();
+        for key in ["abcd", "abce", "b"] {
+            tree.insert(key.chars(), ());
+        }
+
+        assert_eq!(
+            tree.stats(),
+            TreeStats {
+                nodes: 7,
+                entries: 3,
+                max_depth: 4,
+                alphabet_size: 5,
+                max_fanout: 2,
+                chain_nodes: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_longest_common_prefix() {
+        let mut tree = PrefixTree::new();
+        assert_eq!(tree.longest_common_prefix(), vec![]);
+        tree.insert("/usr/lib".chars(), ());
+        assert_eq!(tree.longest_common_prefix().len(), 8);
+        tree.insert("/usr/local".chars(), ());
+        let prefix: String = tree.longest_common_prefix().into_iter().collect();
+        assert_eq!(prefix, "/usr/l");
+        tree.insert("/".chars(), ());
+        assert_eq!(tree.longest_common_prefix(), vec!['/']);
+    }
+}
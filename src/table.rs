@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::hash::{BuildHasher, Hash};
+
+use crate::PrefixTree;
+
+/// A tree exported as a table of states with `FANOUT` transitions each, e.g. to be embedded into
+/// generated code. Identical subtrees share their states, so the table is the minimal acceptor of
+/// the keys. The root is state 0; since no transition leads back to the root, 0 also stands for
+/// "no transition"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionTable<V, const FANOUT: usize> {
+    /// The state reached from every state by every item index
+    pub transitions: Vec<[u32; FANOUT]>,
+    /// The value at the keys that end in every state
+    pub accept: Vec<Option<V>>,
+}
+
+/// The reason a tree couldn't be exported as a `TransitionTable`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableError {
+    /// A key item was mapped to an index that doesn't fit into the fanout
+    IndexOutOfRange(usize),
+    /// Two children of a node were mapped to the same index
+    IndexCollision(usize),
+    /// The states can't be numbered with `u32`
+    TooManyStates,
+}
+
+impl Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IndexOutOfRange(index) => write!(f, "item index {} is out of range", index),
+            Self::IndexCollision(index) => {
+                write!(f, "several items of a node have index {}", index)
+            }
+            Self::TooManyStates => write!(f, "too many states for a transition table"),
+        }
+    }
+}
+
+impl Error for TableError {}
+
+impl<V, const FANOUT: usize> TransitionTable<V, FANOUT> {
+    /// Returns the value at the key given as a sequence of item indices, like the
+    /// generated matcher would
+    pub fn get(&self, indices: impl IntoIterator<Item = usize>) -> Option<&V> {
+        let mut state = 0;
+        for index in indices {
+            state = *self.transitions[state].get(index)? as usize;
+            if state == 0 {
+                return None;
+            }
+        }
+        self.accept[state].as_ref()
+    }
+}
+
+impl<K: Hash + Eq, V: Hash + Eq + Clone, S: BuildHasher + Clone> PrefixTree<K, V, S> {
+    /// Exports the tree as a minimal `TransitionTable`, mapping every key item to its transition
+    /// with `index`. Fails if an index doesn't fit into `FANOUT`, if two children of a node get
+    /// the same index or if there are too many states
+    pub fn to_transition_table<const FANOUT: usize>(
+        &self,
+        mut index: impl FnMut(&K) -> usize,
+    ) -> Result<TransitionTable<V, FANOUT>, TableError> {
+        let mut builder = Builder {
+            table: TransitionTable {
+                transitions: vec![[0; FANOUT]],
+                accept: vec![self.value.clone()],
+            },
+            states: HashMap::new(),
+        };
+        builder.table.transitions[0] = builder.row(self, &mut index)?;
+        Ok(builder.table)
+    }
+}
+
+struct Builder<V, const FANOUT: usize> {
+    table: TransitionTable<V, FANOUT>,
+    /// The state of every distinct subtree added so far, by its transitions and value
+    states: HashMap<([u32; FANOUT], Option<V>), u32>,
+}
+
+impl<V: Hash + Eq + Clone, const FANOUT: usize> Builder<V, FANOUT> {
+    /// Returns the transitions from the node, adding the states of its subtrees
+    fn row<K: Hash + Eq, S: BuildHasher + Clone>(
+        &mut self,
+        root: &PrefixTree<K, V, S>,
+        index: &mut impl FnMut(&K) -> usize,
+    ) -> Result<[u32; FANOUT], TableError> {
+        let mut row = [0; FANOUT];
+        for (key, subtree) in &root.subtrees {
+            let item = index(key);
+            if item >= FANOUT {
+                return Err(TableError::IndexOutOfRange(item));
+            }
+            // No transition leads to the root, so a taken slot is never 0
+            if row[item] != 0 {
+                return Err(TableError::IndexCollision(item));
+            }
+            let row_of_subtree = self.row(subtree, index)?;
+            row[item] = self.state(row_of_subtree, &subtree.value)?;
+        }
+        Ok(row)
+    }
+
+    /// Returns the state with the transitions and the value, adding it if there's none yet
+    fn state(&mut self, row: [u32; FANOUT], value: &Option<V>) -> Result<u32, TableError> {
+        let signature = (row, value.clone());
+        if let Some(&state) = self.states.get(&signature) {
+            return Ok(state);
+        }
+        let state =
+            u32::try_from(self.table.transitions.len()).map_err(|_| TableError::TooManyStates)?;
+        self.table.transitions.push(row);
+        self.table.accept.push(value.clone());
+        self.states.insert(signature, state);
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transition_table() {
+        let mut keywords = PrefixTree::new();
+        for (keyword, token) in [("if", 1), ("in", 2), ("for", 3), ("fn", 2), ("for_", 4)] {
+            keywords.insert(keyword.bytes(), token);
+        }
+
+        let index = |item: &u8| match item {
+            b'_' => 26,
+            item => (item - b'a') as usize,
+        };
+        let table: TransitionTable<_, 27> = keywords.to_transition_table(index).unwrap();
+        let indices = |keyword: &str| keyword.bytes().map(|item| index(&item)).collect::<Vec<_>>();
+        for (keyword, token) in [("if", 1), ("in", 2), ("fn", 2), ("for", 3), ("for_", 4)] {
+            assert_eq!(table.get(indices(keyword)), Some(&token));
+        }
+        assert_eq!(table.get(indices("fo")), None);
+        assert_eq!(table.get(indices("ifs")), None);
+        // The root, "i", "f", "fo", "for", the leaves with 1 and 4, and one shared leaf with 2
+        assert_eq!(table.transitions.len(), 8);
+        assert!(matches!(
+            keywords.to_transition_table::<6>(index),
+            Err(TableError::IndexOutOfRange(_))
+        ));
+        // "fn" and "for" both start with 'f', but 'n' and 'o' now share an index
+        let folded = |item: &u8| index(item).min(13);
+        assert_eq!(
+            keywords.to_transition_table::<27>(folded),
+            Err(TableError::IndexCollision(13))
+        );
+    }
+}
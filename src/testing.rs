@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::{Op, PrefixTree};
+
+/// A prefix tree paired with a flat map of whole keys to values that receives every operation as
+/// well; after each step the two are asserted to agree, so a test panics at the first operation
+/// where the tree goes wrong
+#[derive(Debug)]
+pub struct Oracle<K: Hash + Eq, V> {
+    tree: PrefixTree<K, V>,
+    model: HashMap<Vec<K>, V>,
+}
+
+impl<K: Hash + Eq + Clone + Debug, V: Clone + PartialEq + Debug> Default for Oracle<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq + Clone + Debug, V: Clone + PartialEq + Debug> Oracle<K, V> {
+    pub fn new() -> Self {
+        Self {
+            tree: PrefixTree::new(),
+            model: HashMap::new(),
+        }
+    }
+
+    /// Applies the operation to both the tree and the map, asserts that they returned the same
+    /// value and then `check`s them
+    pub fn apply(&mut self, op: Op<K, V>) -> Option<V> {
+        let expected = match op.clone() {
+            Op::Insert(key, value) => self.model.insert(key, value),
+            Op::RemoveExactMatch(key) => self.model.remove(&key),
+            Op::RemoveByShortestPrefix(key) => self
+                .shortest_prefix(&key)
+                .and_then(|prefix| self.model.remove(&prefix)),
+        };
+        let actual = self.tree.apply(op.clone());
+        assert_eq!(actual, expected, "{:?} returned a wrong value", op);
+        self.check();
+        actual
+    }
+
+    pub fn insert(&mut self, key: impl IntoIterator<Item = K>, value: V) -> Option<V> {
+        self.apply(Op::Insert(key.into_iter().collect(), value))
+    }
+
+    pub fn remove_exact_match(&mut self, key: impl IntoIterator<Item = K>) -> Option<V> {
+        self.apply(Op::RemoveExactMatch(key.into_iter().collect()))
+    }
+
+    pub fn remove_by_shortest_prefix(&mut self, key: impl IntoIterator<Item = K>) -> Option<V> {
+        self.apply(Op::RemoveByShortestPrefix(key.into_iter().collect()))
+    }
+
+    /// Looks the key up in the tree, asserting that the map has the same value at it
+    pub fn get_exact_match(&self, key: &[K]) -> Option<&V> {
+        let actual = self.tree.get_exact_match(key);
+        assert_eq!(
+            actual,
+            self.model.get(key),
+            "wrong exact match of {:?}",
+            key
+        );
+        actual
+    }
+
+    /// Looks the shortest prefix of the key up in the tree, asserting that the shortest prefix of
+    /// the key found in the map has the same value
+    pub fn get_by_shortest_prefix(&self, key: &[K]) -> Option<&V> {
+        let actual = self.tree.get_by_shortest_prefix(key);
+        let expected = self.shortest_prefix(key).map(|prefix| &self.model[&prefix]);
+        assert_eq!(actual, expected, "wrong shortest prefix match of {:?}", key);
+        actual
+    }
+
+    /// Asserts that the tree holds exactly the entries of the map and has no branches without
+    /// values
+    pub fn check(&self) {
+        assert_eq!(self.tree.len(), self.model.len(), "wrong number of entries");
+        for (key, value) in &self.model {
+            assert_eq!(
+                self.tree.get_exact_match(key),
+                Some(value),
+                "wrong value at {:?}",
+                key
+            );
+        }
+        assert!(
+            !has_dead_branches(&self.tree),
+            "the tree kept dead branches"
+        );
+    }
+
+    pub fn tree(&self) -> &PrefixTree<K, V> {
+        &self.tree
+    }
+
+    pub fn model(&self) -> &HashMap<Vec<K>, V> {
+        &self.model
+    }
+
+    fn shortest_prefix(&self, key: &[K]) -> Option<Vec<K>> {
+        (0..=key.len())
+            .map(|len| &key[..len])
+            .find(|prefix| self.model.contains_key(*prefix))
+            .map(<[K]>::to_vec)
+    }
+}
+
+fn has_dead_branches<K: Hash + Eq, V>(root: &PrefixTree<K, V>) -> bool {
+    root.subtrees.values().any(|subtree| {
+        (subtree.value.is_none() && subtree.subtrees.is_empty()) || has_dead_branches(subtree)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oracle() {
+        let mut oracle = Oracle::new();
+        oracle.insert("ab".chars(), 1);
+        oracle.insert("abc".chars(), 2);
+        oracle.insert("b".chars(), 3);
+        assert_eq!(oracle.get_by_shortest_prefix(&['a', 'b', 'c']), Some(&1));
+        assert_eq!(oracle.remove_by_shortest_prefix("abcd".chars()), Some(1));
+        assert_eq!(oracle.remove_exact_match("abc".chars()), Some(2));
+        assert_eq!(oracle.get_exact_match(&['a', 'b']), None);
+        assert_eq!(oracle.model().len(), 1);
+    }
+}
@@ -0,0 +1,177 @@
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+use crate::{IntoKeyItem, PrefixTree};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Entry<V> {
+    value: V,
+    deleted: bool,
+}
+
+/// A prefix tree where removal only marks entries as deleted (leaving a tombstone), so they can be
+/// listed and restored until `compact` removes them for good. Deleted entries are invisible to the
+/// lookups
+#[derive(Debug, PartialEq, Eq)]
+pub struct TombstoneTree<K: Hash + Eq, V> {
+    tree: PrefixTree<K, Entry<V>>,
+}
+
+impl<K: Hash + Eq, V> Default for TombstoneTree<K, V> {
+    fn default() -> Self {
+        Self {
+            tree: PrefixTree::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> TombstoneTree<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same as `PrefixTree::insert`; replacing a deleted entry drops it and returns `None`
+    pub fn insert<I: IntoKeyItem<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+        value: V,
+    ) -> Option<V> {
+        let entry = Entry {
+            value,
+            deleted: false,
+        };
+        self.tree
+            .insert(sequence, entry)
+            .filter(|entry| !entry.deleted)
+            .map(|entry| entry.value)
+    }
+
+    pub fn get_exact_match<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        self.tree
+            .get_exact_match(sequence)
+            .filter(|entry| !entry.deleted)
+            .map(|entry| &entry.value)
+    }
+
+    /// Same as `PrefixTree::get_by_shortest_prefix`, skipping the deleted entries
+    pub fn get_by_shortest_prefix<I: Borrow<K>>(
+        &self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> Option<&V> {
+        let mut sequence = sequence.into_iter();
+        let mut root = &self.tree;
+        loop {
+            if let Some(entry) = &root.value {
+                if !entry.deleted {
+                    return Some(&entry.value);
+                }
+            }
+            root = sequence
+                .next()
+                .and_then(|item| root.subtrees.get(item.borrow()))?;
+        }
+    }
+
+    /// Marks the entry as deleted; returns `false` if there was no live entry at the key
+    pub fn remove_exact_match<I: Borrow<K>>(
+        &mut self,
+        sequence: impl IntoIterator<Item = I>,
+    ) -> bool {
+        match self.tree.get_exact_match_mut(sequence) {
+            Some(entry) if !entry.deleted => {
+                entry.deleted = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Brings a deleted entry back; returns `false` if there was no deleted entry at the key
+    pub fn restore<I: Borrow<K>>(&mut self, sequence: impl IntoIterator<Item = I>) -> bool {
+        match self.tree.get_exact_match_mut(sequence) {
+            Some(entry) if entry.deleted => {
+                entry.deleted = false;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Permanently removes all the deleted entries and returns how many there were
+    pub fn compact(&mut self) -> usize {
+        let removed = compact(&mut self.tree);
+        self.tree.canonicalize();
+        removed
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> TombstoneTree<K, V> {
+    /// Returns the keys and values of all the deleted entries that haven't been compacted yet
+    pub fn deleted_entries(&self) -> Vec<(Vec<K>, &V)> {
+        let mut entries = Vec::new();
+        collect_deleted(&self.tree, &mut Vec::new(), &mut entries);
+        entries
+    }
+}
+
+fn compact<K: Hash + Eq, V>(root: &mut PrefixTree<K, Entry<V>>) -> usize {
+    let mut removed = 0;
+    if root.value.as_ref().map_or(false, |entry| entry.deleted) {
+        root.value = None;
+        removed += 1;
+    }
+    for subtree in root.subtrees.values_mut() {
+        removed += compact(subtree);
+    }
+    removed
+}
+
+fn collect_deleted<'a, K: Hash + Eq + Clone, V>(
+    root: &'a PrefixTree<K, Entry<V>>,
+    path: &mut Vec<K>,
+    entries: &mut Vec<(Vec<K>, &'a V)>,
+) {
+    if let Some(entry) = &root.value {
+        if entry.deleted {
+            entries.push((path.clone(), &entry.value));
+        }
+    }
+    for (key, subtree) in &root.subtrees {
+        path.push(key.clone());
+        collect_deleted(subtree, path, entries);
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tombstone_tree() {
+        let mut tree = TombstoneTree::new();
+        tree.insert("a".chars(), 1);
+        tree.insert("ab".chars(), 2);
+
+        assert!(tree.remove_exact_match("a".chars()));
+        assert!(!tree.remove_exact_match("a".chars()));
+        assert_eq!(tree.get_exact_match("a".chars()), None);
+        assert_eq!(tree.get_by_shortest_prefix("abc".chars()), Some(&2));
+        assert_eq!(tree.deleted_entries(), vec![(vec!['a'], &1)]);
+
+        assert!(tree.restore("a".chars()));
+        assert_eq!(tree.get_by_shortest_prefix("abc".chars()), Some(&1));
+
+        tree.remove_exact_match("ab".chars());
+        assert_eq!(tree.compact(), 1);
+        assert!(!tree.restore("ab".chars()));
+        assert_eq!(tree.deleted_entries(), vec![]);
+
+        let mut expected = TombstoneTree::new();
+        expected.insert("a".chars(), 1);
+        assert_eq!(tree, expected);
+    }
+}
@@ -0,0 +1,141 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::hash::BuildHasher;
+
+use crate::PrefixTree;
+
+/// A malformed UTF-8 sequence met by a lookup with byte input; `position` is the offset of its
+/// first byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidUtf8 {
+    pub position: usize,
+}
+
+impl Display for InvalidUtf8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid UTF-8 sequence at byte {}", self.position)
+    }
+}
+
+impl Error for InvalidUtf8 {}
+
+/// Decodes characters from bytes one at a time
+struct Utf8Chars<B> {
+    bytes: B,
+    position: usize,
+}
+
+impl<B: Iterator<Item = u8>> Iterator for Utf8Chars<B> {
+    type Item = Result<char, InvalidUtf8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.bytes.next()?;
+        let error = InvalidUtf8 {
+            position: self.position,
+        };
+        self.position += 1;
+        let (len, min, mut code) = match first {
+            0x00..=0x7f => return Some(Ok(first as char)),
+            0xc0..=0xdf => (2, 0x80, first as u32 & 0x1f),
+            0xe0..=0xef => (3, 0x800, first as u32 & 0x0f),
+            0xf0..=0xf7 => (4, 0x10000, first as u32 & 0x07),
+            _ => return Some(Err(error)),
+        };
+        for _ in 1..len {
+            match self.bytes.next() {
+                Some(byte) if byte & 0xc0 == 0x80 => code = code << 6 | (byte & 0x3f) as u32,
+                _ => return Some(Err(error)),
+            }
+            self.position += 1;
+        }
+        if code < min {
+            // An overlong encoding
+            return Some(Err(error));
+        }
+        // Rejects the surrogates and the codes above the Unicode range
+        Some(char::from_u32(code).ok_or(error))
+    }
+}
+
+/// Runs the lookup on the characters decoded from the bytes, failing if the lookup read a malformed
+/// sequence
+fn with_chars<'a, V>(
+    bytes: impl IntoIterator<Item = u8>,
+    lookup: impl FnOnce(&mut dyn Iterator<Item = char>) -> Option<&'a V>,
+) -> Result<Option<&'a V>, InvalidUtf8> {
+    let mut error = None;
+    let mut chars = Utf8Chars {
+        bytes: bytes.into_iter(),
+        position: 0,
+    }
+    .map_while(|decoded| decoded.map_err(|invalid| error = Some(invalid)).ok());
+    let found = lookup(&mut chars);
+    match error {
+        Some(error) => Err(error),
+        None => Ok(found),
+    }
+}
+
+impl<V, S: BuildHasher + Clone> PrefixTree<char, V, S> {
+    /// Same as `get_exact_match`, but takes the key as UTF-8 bytes, which are decoded as the
+    /// lookup goes
+    pub fn get_exact_match_utf8(
+        &self,
+        bytes: impl IntoIterator<Item = u8>,
+    ) -> Result<Option<&V>, InvalidUtf8> {
+        with_chars(bytes, |chars| self.get_exact_match(chars))
+    }
+
+    /// Same as `get_by_shortest_prefix`, but takes the sequence as UTF-8 bytes; only the bytes up
+    /// to the match are decoded (and checked)
+    pub fn get_by_shortest_prefix_utf8(
+        &self,
+        bytes: impl IntoIterator<Item = u8>,
+    ) -> Result<Option<&V>, InvalidUtf8> {
+        with_chars(bytes, |chars| self.get_by_shortest_prefix(chars))
+    }
+
+    /// Same as `get_by_longest_prefix`, but takes the sequence as UTF-8 bytes; the bytes are
+    /// decoded (and checked) until the lookup leaves the tree
+    pub fn get_by_longest_prefix_utf8(
+        &self,
+        bytes: impl IntoIterator<Item = u8>,
+    ) -> Result<Option<&V>, InvalidUtf8> {
+        with_chars(bytes, |chars| self.get_by_longest_prefix(chars))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf8_lookups() {
+        let mut tree = PrefixTree::new();
+        tree.insert("héllo".chars(), 1);
+        tree.insert("hé".chars(), 2);
+        tree.insert("日本".chars(), 3);
+
+        let bytes = |text: &str| text.as_bytes().to_vec();
+        assert_eq!(tree.get_exact_match_utf8(bytes("héllo")), Ok(Some(&1)));
+        assert_eq!(tree.get_exact_match_utf8(bytes("日本")), Ok(Some(&3)));
+        assert_eq!(
+            tree.get_by_longest_prefix_utf8(bytes("héllo!")),
+            Ok(Some(&1))
+        );
+        assert_eq!(
+            tree.get_exact_match_utf8(vec![b'h', 0xc3]),
+            Err(InvalidUtf8 { position: 1 })
+        );
+        // An overlong encoding of 'é'
+        assert_eq!(
+            tree.get_exact_match_utf8(vec![b'h', 0xe0, 0x83, 0xa9]),
+            Err(InvalidUtf8 { position: 1 })
+        );
+        // The lookup stops at "hé" before reaching the malformed byte
+        assert_eq!(
+            tree.get_by_shortest_prefix_utf8(vec![b'h', 0xc3, 0xa9, 0xff]),
+            Ok(Some(&2))
+        );
+    }
+}